@@ -0,0 +1,12 @@
+//! a language server for CVLDoc, speaking JSON-RPC over stdio.
+
+use color_eyre::Result;
+use cvldoc_parser_core::server;
+use lsp_server::Connection;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    server::run(connection)?;
+    io_threads.join()?;
+    Ok(())
+}
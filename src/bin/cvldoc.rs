@@ -0,0 +1,272 @@
+//! a command-line interface to the parser, for CI and shell scripts.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{bail, eyre, WrapErr};
+use color_eyre::Result;
+use cvldoc_parser_core::outline::{outline, Section};
+use cvldoc_parser_core::parse::builder::Builder;
+use cvldoc_parser_core::project::ProjectBuilder;
+use cvldoc_parser_core::unused::UnusedConfig;
+use cvldoc_parser_core::{export, Ast, SpecFile};
+use lsp_types::DiagnosticSeverity;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "cvldoc",
+    version,
+    about = "Parses CVLDoc documentation of CVL specs"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// print the parsed elements of each file as JSON
+    Parse(Inputs),
+    /// print the diagnostics of each file. exits with an error if any of them is an error.
+    Check(Inputs),
+    /// print the sections and elements of each file as a tree
+    Outline(Inputs),
+    /// render the documentation of the files
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Md)]
+        format: Format,
+        /// the file to write to, instead of the standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        inputs: Inputs,
+    },
+}
+
+#[derive(clap::Args)]
+struct Inputs {
+    /// spec files, directories to search for `.spec` files, or glob patterns
+    #[arg(required = true)]
+    inputs: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Md,
+    Html,
+    Json,
+}
+
+/// a file given on the command line, and its parsed contents
+struct Input {
+    path: PathBuf,
+    spec: SpecFile,
+}
+
+impl Input {
+    fn display_path(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+/// the spec files named by `inputs`, sorted and without duplicates
+fn expand(inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        let found = if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            let pattern = if path.is_dir() {
+                path.join("**").join("*.spec").display().to_string()
+            } else {
+                input.clone()
+            };
+            glob::glob(&pattern)
+                .wrap_err_with(|| format!("invalid pattern: {input}"))?
+                .filter_map(|entry| entry.ok())
+                .filter(|path| path.is_file())
+                .collect()
+        };
+
+        if found.is_empty() {
+            bail!("no spec files found for {input}");
+        }
+        paths.extend(found);
+    }
+
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+fn load(inputs: &Inputs) -> Result<Vec<Input>> {
+    expand(&inputs.inputs)?
+        .into_iter()
+        .map(|path| {
+            let src = std::fs::read_to_string(&path)
+                .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+            let spec = Builder::new(&src)
+                .build_spec_file()
+                .map_err(|e| eyre!("failed to parse {}: {e}", path.display()))?;
+            Ok(Input { path, spec })
+        })
+        .collect()
+}
+
+fn parse(inputs: &[Input]) -> Result<String> {
+    #[derive(Serialize)]
+    struct Parsed<'a> {
+        path: String,
+        #[serde(flatten)]
+        spec: &'a SpecFile,
+    }
+
+    let parsed = inputs
+        .iter()
+        .map(|input| Parsed {
+            path: input.display_path(),
+            spec: &input.spec,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::to_string_pretty(&parsed)? + "\n")
+}
+
+/// prints each diagnostic as `path:line:column: severity: message`, with 1-based
+/// lines and columns, and returns whether any of them is an error.
+/// the inputs are checked together with the files they import, as in `SpecProject::diagnostics`,
+/// but only the diagnostics of the inputs themselves are printed.
+fn check(inputs: &[Input], out: &mut impl Write) -> Result<bool> {
+    let project = ProjectBuilder::new(inputs.iter().map(|input| &input.path)).build()?;
+    let diagnostics = project.diagnostics(&UnusedConfig::default());
+    let mut has_errors = false;
+
+    for input in inputs {
+        let Some(file) = project.file(&input.path) else {
+            continue;
+        };
+        let of_input = diagnostics
+            .iter()
+            .filter(|(path, _)| *path == file.path)
+            .map(|(_, diagnostic)| diagnostic);
+
+        for diagnostic in of_input {
+            let severity = match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => {
+                    has_errors = true;
+                    "error"
+                }
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::INFORMATION) => "info",
+                _ => "hint",
+            };
+            let start = diagnostic.range.start;
+            writeln!(
+                out,
+                "{}:{}:{}: {severity}: {}",
+                input.display_path(),
+                start.line + 1,
+                start.character + 1,
+                diagnostic.message
+            )?;
+        }
+    }
+
+    Ok(has_errors)
+}
+
+fn print_outline(inputs: &[Input], out: &mut impl Write) -> Result<()> {
+    fn print_section(section: &Section, depth: usize, out: &mut impl Write) -> Result<()> {
+        let indent = "  ".repeat(depth);
+
+        for element in &section.elements {
+            if let Ast::FreeFormComment { .. } | Ast::Import { .. } = element.ast {
+                continue;
+            }
+            let title = element.title().unwrap_or_default();
+            writeln!(out, "{indent}{} {title}", element.ast)?;
+        }
+        for subsection in &section.subsections {
+            let hashes = "#".repeat(subsection.level);
+            writeln!(out, "{indent}{hashes} {}", subsection.title)?;
+            print_section(subsection, depth + 1, out)?;
+        }
+
+        Ok(())
+    }
+
+    for input in inputs {
+        writeln!(out, "{}", input.display_path())?;
+        print_section(&outline(&input.spec.elements), 1, out)?;
+    }
+
+    Ok(())
+}
+
+/// runs the command, returning whether it succeeded
+fn run(cli: Cli) -> Result<bool> {
+    let mut stdout = std::io::stdout().lock();
+
+    match cli.command {
+        Command::Parse(inputs) => {
+            let inputs = load(&inputs)?;
+            write!(stdout, "{}", parse(&inputs)?)?;
+        }
+        Command::Check(inputs) => {
+            let inputs = load(&inputs)?;
+            if check(&inputs, &mut stdout)? {
+                return Ok(false);
+            }
+        }
+        Command::Outline(inputs) => {
+            let inputs = load(&inputs)?;
+            print_outline(&inputs, &mut stdout)?;
+        }
+        Command::Export {
+            format,
+            output,
+            inputs,
+        } => {
+            let inputs = load(&inputs)?;
+            let paths = inputs
+                .iter()
+                .map(|input| input.display_path())
+                .collect::<Vec<_>>();
+            let files = inputs
+                .iter()
+                .zip(&paths)
+                .map(|(input, path)| (path.as_str(), input.spec.elements.as_slice()))
+                .collect::<Vec<_>>();
+
+            let rendered = match format {
+                Format::Md => export::to_markdown(&files),
+                Format::Html => export::to_html(&files),
+                Format::Json => export::to_json(&files) + "\n",
+            };
+
+            match output {
+                Some(output) => std::fs::write(&output, rendered)
+                    .wrap_err_with(|| format!("failed to write {}", output.display()))?,
+                None => write!(stdout, "{rendered}")?,
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! flattens a spec and the files it imports into a single file.
+
+use crate::project::SpecProject;
+use crate::util::Span;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// a span of the bundle that was copied from one of the original files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceMapping {
+    /// the span in [Bundle::src]
+    pub output: Span,
+    pub path: PathBuf,
+    /// the span in the original file
+    pub original: Span,
+}
+
+/// a single spec holding the contents of a file and of every file it imports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Bundle {
+    pub src: String,
+    /// the spans of [Bundle::src] that were copied from the original files, in order.
+    /// the banners between files are not copied from any file.
+    pub source_map: Vec<SourceMapping>,
+}
+
+/// the files reachable from `root`, with every file after the files it imports
+fn dependency_order(project: &SpecProject, root: usize) -> Vec<usize> {
+    fn visit(project: &SpecProject, file: usize, visited: &mut Vec<bool>, order: &mut Vec<usize>) {
+        if std::mem::replace(&mut visited[file], true) {
+            return;
+        }
+        for &imported in project.files[file].resolved_imports.iter().flatten() {
+            visit(project, imported, visited, order);
+        }
+        order.push(file);
+    }
+
+    let mut visited = vec![false; project.files.len()];
+    let mut order = Vec::new();
+    visit(project, root, &mut visited, &mut order);
+    order
+}
+
+/// widens the span of a statement to its whole line, if nothing else is on that line
+fn whole_line(chars: &[char], span: &Span) -> Span {
+    let is_blank = |ch: &&char| matches!(ch, ' ' | '\t' | '\r');
+
+    let before = chars[..span.start]
+        .iter()
+        .rev()
+        .take_while(is_blank)
+        .count();
+    let after = chars[span.end..].iter().take_while(is_blank).count();
+
+    let starts_line = span.start == before || chars[span.start - before - 1] == '\n';
+    let end = span.end + after;
+    match chars.get(end) {
+        Some('\n') if starts_line => span.start - before..end + 1,
+        None if starts_line => span.start - before..end,
+        _ => span.clone(),
+    }
+}
+
+impl Bundle {
+    /// bundles `root` with the files it imports, directly or not. each file appears once,
+    /// after the files it imports, and starts with a `////` banner holding its path.
+    /// the resolved `import` statements are removed, and all other text, including
+    /// documentation, is kept as it is. imports that could not be resolved are kept.
+    pub fn new(project: &SpecProject, root: usize) -> Bundle {
+        let root_dir = project.files[root].path.parent().unwrap_or(Path::new(""));
+        let mut bundle = Bundle::default();
+        let mut output_len = 0;
+
+        for index in dependency_order(project, root) {
+            let file = &project.files[index];
+            let chars = file.src.chars().collect::<Vec<_>>();
+
+            let shown_path = file.path.strip_prefix(root_dir).unwrap_or(&file.path);
+            let banner = format!("//// ## {}\n\n", shown_path.display());
+            output_len += banner.chars().count();
+            bundle.src.push_str(&banner);
+
+            let removed = file
+                .spec
+                .imports
+                .iter()
+                .zip(&file.resolved_imports)
+                .filter(|(_, resolved)| resolved.is_some())
+                .map(|(import, _)| whole_line(&chars, &import.span));
+
+            let mut kept = Vec::new();
+            let mut start = 0;
+            for removed in removed {
+                kept.push(start..removed.start);
+                start = removed.end;
+            }
+            kept.push(start..chars.len());
+
+            for original in kept.into_iter().filter(|span| !span.is_empty()) {
+                let len = original.len();
+                bundle.src.extend(&chars[original.clone()]);
+                bundle.source_map.push(SourceMapping {
+                    output: output_len..output_len + len,
+                    path: file.path.clone(),
+                    original,
+                });
+                output_len += len;
+            }
+
+            if !bundle.src.ends_with('\n') {
+                bundle.src.push('\n');
+                output_len += 1;
+            }
+            bundle.src.push('\n');
+            output_len += 1;
+        }
+
+        bundle
+    }
+
+    /// the file and the char offset in it that the char at `offset` in the bundle was
+    /// copied from, if it was copied from any file
+    pub fn original_location(&self, offset: usize) -> Option<(&Path, usize)> {
+        let mapping = self
+            .source_map
+            .iter()
+            .find(|mapping| mapping.output.contains(&offset))?;
+
+        let original = mapping.original.start + (offset - mapping.output.start);
+        Some((mapping.path.as_path(), original))
+    }
+
+    /// the span in its original file of a span of the bundle, if it was all copied
+    /// from a single contiguous span of that file
+    pub fn original_span(&self, span: &Span) -> Option<(&Path, Span)> {
+        let mapping = self
+            .source_map
+            .iter()
+            .find(|mapping| mapping.output.start <= span.start && span.end <= mapping.output.end)?;
+
+        let start = mapping.original.start + (span.start - mapping.output.start);
+        Some((mapping.path.as_path(), start..start + span.len()))
+    }
+}
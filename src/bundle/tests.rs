@@ -0,0 +1,127 @@
+use super::Bundle;
+use crate::parse::builder::Builder;
+use crate::project::fs::InMemoryFileSystem;
+use crate::project::{ProjectBuilder, SpecProject};
+use crate::util::ByteSpan;
+use crate::Ast;
+use indoc::{formatdoc, indoc};
+use std::path::Path;
+
+fn project() -> SpecProject {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        indoc! {"
+            import \"lib/erc20.spec\";
+            import \"shared.spec\";
+            import \"missing.spec\";
+
+            rule main { assert MAX() > 0; }
+        "},
+    );
+    file_system.insert(
+        "/specs/lib/erc20.spec",
+        indoc! {"
+            import \"../shared.spec\";
+
+            /// @title transfers preserve the total supply
+            rule transferPreservesSupply { }
+        "},
+    );
+    file_system.insert(
+        "/specs/shared.spec",
+        "definition MAX() returns uint = 1000;",
+    );
+
+    ProjectBuilder::new(["/specs/main.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn files_appear_once_after_their_imports() {
+    let project = project();
+    let bundle = Bundle::new(&project, 0);
+
+    let expected = formatdoc! {"
+        //// ## shared.spec
+
+        definition MAX() returns uint = 1000;
+
+        //// ## lib/erc20.spec
+
+
+        /// @title transfers preserve the total supply
+        rule transferPreservesSupply {{ }}
+
+        //// ## main.spec
+
+        import \"missing.spec\";
+
+        rule main {{ assert MAX() > 0; }}
+
+    "};
+    assert_eq!(bundle.src, expected);
+
+    let elements = Builder::new(&bundle.src).build().unwrap();
+    let kinds = elements
+        .iter()
+        .map(|element| element.ast.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "freeform comment",
+            "definition",
+            "freeform comment",
+            "rule",
+            "freeform comment",
+            "import",
+            "rule",
+        ]
+    );
+    assert_eq!(
+        elements[3].title().as_deref(),
+        Some("transfers preserve the total supply")
+    );
+    assert!(matches!(&elements[5].ast, Ast::Import { imported } if imported == "missing.spec"));
+}
+
+#[test]
+fn spans_map_back_to_the_original_files() {
+    let project = project();
+    let bundle = Bundle::new(&project, 0);
+
+    let paths = bundle
+        .source_map
+        .iter()
+        .map(|mapping| mapping.path.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/specs/shared.spec",
+            "/specs/lib/erc20.spec",
+            "/specs/main.spec"
+        ]
+    );
+
+    let elements = Builder::new(&bundle.src).build().unwrap();
+    let rule = &elements[6];
+    let (path, span) = bundle.original_span(&rule.element_span).unwrap();
+    assert_eq!(path, Path::new("/specs/main.spec"));
+    assert_eq!(
+        span.byte_slice(&project.files[0].src),
+        Some("rule main { assert MAX() > 0; }")
+    );
+
+    let (path, offset) = bundle
+        .original_location(elements[1].element_span.start)
+        .unwrap();
+    assert_eq!(path, Path::new("/specs/shared.spec"));
+    assert_eq!(offset, 0);
+
+    // banners are not copied from any file
+    assert_eq!(bundle.original_location(0), None);
+}
@@ -0,0 +1,106 @@
+//! warnings for uses of elements marked `@deprecated`.
+
+use crate::symbols::shadowing_param;
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, CvlElement};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag};
+
+#[cfg(test)]
+mod tests;
+
+/// a use of a deprecated element
+#[derive(Debug, Clone)]
+pub struct DeprecatedUse<'a> {
+    pub deprecated: &'a CvlElement,
+    /// the span of the `use` statement, or of the name in a call
+    pub span: Span,
+}
+
+impl DeprecatedUse<'_> {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let name = self.deprecated.ast.name().unwrap_or_default();
+        let message = match self.deprecated.deprecated() {
+            Some(reason) if !reason.is_empty() => format!("{name} is deprecated: {reason}"),
+            _ => format!("{name} is deprecated"),
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message,
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            ..Default::default()
+        }
+    }
+}
+
+/// finds the uses in `elements` of deprecated elements, which may be declared
+/// either in `elements` or in any of the `imported` files. these are:
+/// - `use rule` and `use invariant` statements of deprecated rules and invariants
+/// - calls to deprecated definitions and functions, and `requireInvariant`s of deprecated invariants,
+///   unless a parameter of the calling element has the same name
+pub fn deprecated_uses<'a>(
+    elements: &'a [CvlElement],
+    imported: &[&'a [CvlElement]],
+) -> Vec<DeprecatedUse<'a>> {
+    let deprecated = std::iter::once(elements)
+        .chain(imported.iter().copied())
+        .flatten()
+        .filter(|element| element.deprecated().is_some())
+        .collect::<Vec<_>>();
+
+    if deprecated.is_empty() {
+        return Vec::new();
+    }
+
+    let find = |name: &str, kind: fn(&Ast) -> bool| {
+        deprecated
+            .iter()
+            .copied()
+            .find(|element| element.ast.name() == Some(name) && kind(&element.ast))
+    };
+
+    let mut uses = Vec::new();
+
+    for element in elements {
+        let used = match &element.ast {
+            Ast::UseRule { name, .. } => find(name, |ast| matches!(ast, Ast::Rule { .. })),
+            Ast::UseInvariant { name, .. } => {
+                find(name, |ast| matches!(ast, Ast::Invariant { .. }))
+            }
+            _ => None,
+        };
+        if let Some(deprecated) = used {
+            uses.push(DeprecatedUse {
+                deprecated,
+                span: element.element_span.clone(),
+            });
+            continue;
+        }
+
+        let is_callable = |ast: &Ast| {
+            matches!(
+                ast,
+                Ast::Definition { .. } | Ast::Function { .. } | Ast::Invariant { .. }
+            )
+        };
+
+        for reference in element.references() {
+            if !reference.is_call || element.ast.name() == Some(reference.name.as_str()) {
+                continue;
+            }
+            if shadowing_param(element, reference.head()).is_some() {
+                continue;
+            }
+
+            if let Some(deprecated) = find(&reference.name, is_callable) {
+                uses.push(DeprecatedUse {
+                    deprecated,
+                    span: reference.span,
+                });
+            }
+        }
+    }
+
+    uses
+}
@@ -0,0 +1,114 @@
+use super::deprecated_uses;
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use indoc::indoc;
+use itertools::Itertools;
+use lsp_types::{DiagnosticSeverity, DiagnosticTag};
+use ropey::Rope;
+
+#[test]
+fn deprecated_elements_are_reported_where_used() {
+    let imported_src = indoc! {"
+        /// @deprecated use transferIntegrity instead
+        /// @since 1.2
+        rule oldTransfer { }
+
+        /// @deprecated
+        invariant oldSolvency() true;
+
+        rule transferIntegrity { }
+    "};
+    let src = indoc! {"
+        use rule oldTransfer;
+        use rule transferIntegrity;
+        use invariant oldSolvency;
+
+        /// @notice the old fee
+        /// @deprecated fees are now computed by the contract
+        definition oldFee(uint x) returns uint = x / 100;
+
+        /// @deprecated
+        function oldHelper() { }
+
+        rule usesThem {
+            uint fee = oldFee(5);
+            oldHelper();
+            requireInvariant oldSolvency();
+            // oldHelper();
+        }
+    "};
+
+    let imported = Builder::new(imported_src).build().unwrap();
+    let elements = Builder::new(src).build().unwrap();
+
+    assert_eq!(
+        imported[0].deprecated(),
+        Some("use transferIntegrity instead")
+    );
+    assert_eq!(imported[0].since(), Some("1.2"));
+    assert_eq!(imported[1].deprecated(), Some(""));
+
+    let uses = deprecated_uses(&elements, &[&imported]);
+    let spans = uses
+        .iter()
+        .map(|deprecated_use| deprecated_use.span.byte_slice(src).unwrap())
+        .collect_vec();
+    assert_eq!(
+        spans,
+        [
+            "use rule oldTransfer;",
+            "use invariant oldSolvency;",
+            "oldFee",
+            "oldHelper",
+            "oldSolvency"
+        ]
+    );
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostics = uses
+        .iter()
+        .map(|deprecated_use| deprecated_use.to_diagnostic(&converter))
+        .collect_vec();
+
+    assert_eq!(
+        diagnostics[0].message,
+        "oldTransfer is deprecated: use transferIntegrity instead"
+    );
+    assert_eq!(diagnostics[1].message, "oldSolvency is deprecated");
+    assert_eq!(diagnostics[2].range.start.line, 12);
+    assert!(diagnostics.iter().all(|diagnostic| {
+        diagnostic.severity == Some(DiagnosticSeverity::WARNING)
+            && diagnostic.tags == Some(vec![DiagnosticTag::DEPRECATED])
+    }));
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    assert!(elements[3].enumerate_diagnostics(converter).is_empty());
+}
+
+#[test]
+fn parameters_shadow_deprecated_elements() {
+    let src = indoc! {"
+        /// @deprecated
+        function f() { }
+
+        rule allMethods(method f) {
+            env e;
+            calldataarg args;
+            f(e, args);
+        }
+
+        rule callsHelper {
+            f();
+        }
+    "};
+
+    let elements = Builder::new(src).build().unwrap();
+    let uses = deprecated_uses(&elements, &[]);
+
+    let deprecated_use = uses.iter().exactly_one().unwrap();
+    let converter = RangeConverter::new(Rope::from_str(src));
+    assert_eq!(
+        deprecated_use.to_diagnostic(&converter).range.start.line,
+        10
+    );
+}
@@ -1,23 +1,47 @@
-use crate::util::RangeConverter;
-use crate::{Ast, CvlElement, DocumentationTag, TagKind};
+use crate::deprecation::deprecated_uses;
+use crate::examples::example_problems;
+use crate::formula::formula_problems;
+use crate::ghosts::GhostReport;
+use crate::graph::DependencyGraph;
+use crate::inherit::inherit_docs;
+use crate::links::{resolve_links, unresolved_link_diagnostics};
+use crate::project::SpecProject;
+use crate::unused::UnusedConfig;
+use crate::util::{RangeConverter, Span};
+use crate::{
+    Ast, CvlElement, DocumentationTag, NestedKind, ParseResult, SpecFile, TagKind, UnattachedDoc,
+    UnattachedReason,
+};
 use lsp_types::{Diagnostic, DiagnosticSeverity};
+use std::path::Path;
 
 impl Ast {
     fn supported_tags(&self) -> &[TagKind] {
         use TagKind::*;
         match self {
-            Ast::Rule { .. } => &[Title, Notice, Dev, Param, Formula],
-            Ast::Invariant { .. } => &[Title, Notice, Dev, Param],
-            Ast::Function { .. } => &[Notice, Dev, Param, Return],
-            Ast::Definition { .. } => &[Notice, Dev, Param, Return],
-            Ast::GhostFunction { .. } | Ast::GhostMapping { .. } => &[Notice, Dev, Param, Return],
+            Ast::Rule { .. } => &[
+                Title, Notice, Dev, Param, Formula, InheritDoc, Deprecated, Since, Example, Req,
+            ],
+            Ast::Invariant { .. } => &[
+                Title, Notice, Dev, Param, InheritDoc, Deprecated, Since, Example, Req,
+            ],
+            Ast::Function { .. } => &[
+                Notice, Dev, Param, Return, InheritDoc, Deprecated, Since, Example,
+            ],
+            Ast::Definition { .. } => &[
+                Notice, Dev, Param, Return, InheritDoc, Deprecated, Since, Example,
+            ],
+            Ast::GhostFunction { .. } | Ast::GhostMapping { .. } => {
+                &[Notice, Dev, Param, Return, Deprecated, Since, Example]
+            }
             Ast::Methods { .. } => &[Notice, Dev],
             Ast::FreeFormComment { .. } => &[Notice, Dev],
+            // these may also hold tags inherited from the rule or invariant they use
+            Ast::UseRule { .. } => &[Title, Notice, Dev, Param, Formula, InheritDoc, Req],
+            Ast::UseInvariant { .. } => &[Title, Notice, Dev, Param, InheritDoc, Req],
             Ast::Import { .. }
             | Ast::Using { .. }
-            | Ast::UseRule { .. }
             | Ast::UseBuiltinRule { .. }
-            | Ast::UseInvariant { .. }
             | Ast::HookSload { .. }
             | Ast::HookSstore { .. }
             | Ast::HookCreate { .. }
@@ -25,11 +49,16 @@ impl Ast {
         }
     }
 
-    fn supports(&self, tag: &TagKind) -> bool {
+    pub(crate) fn supports(&self, tag: &TagKind) -> bool {
         self.supported_tags().contains(tag)
     }
 
     fn defines_param(&self, param_name: &str) -> bool {
+        // the parameters are declared by the rule or invariant being used, which may be in another file
+        if matches!(self, Ast::UseRule { .. } | Ast::UseInvariant { .. }) {
+            return true;
+        }
+
         if let Some(params) = self.params() {
             params.iter().any(|param| param.name == param_name)
         } else {
@@ -38,6 +67,19 @@ impl Ast {
     }
 }
 
+impl NestedKind {
+    fn supported_tags(&self) -> &[TagKind] {
+        use TagKind::*;
+        match self {
+            NestedKind::MethodsEntry | NestedKind::Preserved | NestedKind::Axiom => &[Notice, Dev],
+        }
+    }
+
+    fn supports(&self, tag: &TagKind) -> bool {
+        self.supported_tags().contains(tag)
+    }
+}
+
 enum DiagSpan<'a> {
     #[allow(unused)]
     EntireDoc,
@@ -97,6 +139,248 @@ impl CvlElement {
             }
         }
 
+        for nested in &self.nested {
+            for tag in &nested.doc {
+                if !nested.kind.supports(&tag.kind) {
+                    let message = format!("this tag is unsupported for {} entries", nested.kind);
+                    add(message, DiagSpan::SingleTag(tag), DiagnosticSeverity::ERROR);
+                }
+            }
+        }
+
         diagnostics
     }
 }
+
+impl UnattachedReason {
+    fn message(self) -> String {
+        match self {
+            UnattachedReason::FollowedByDocumentation => {
+                "documentation is shadowed by the documentation that follows it".to_string()
+            }
+            UnattachedReason::FollowedByFreeForm => {
+                "documentation is followed by a freeform comment, and is not associated with any element".to_string()
+            }
+            UnattachedReason::EndOfFile => {
+                "documentation at the end of the file is not associated with any element"
+                    .to_string()
+            }
+            UnattachedReason::TooManyBlankLines { found, max } => format!(
+                "documentation is separated from its element by {found} blank lines (at most {max} allowed)"
+            ),
+            UnattachedReason::InterveningComment => {
+                "documentation is separated from its element by a comment".to_string()
+            }
+            UnattachedReason::InterveningParseError => {
+                "documentation is separated from its element by code that failed to parse"
+                    .to_string()
+            }
+            UnattachedReason::EndOfBlock => {
+                "documentation at the end of a block is not associated with any entry".to_string()
+            }
+            UnattachedReason::InsideEntry => {
+                "documentation inside an entry of a block is not associated with any entry"
+                    .to_string()
+            }
+            UnattachedReason::NoPreviousElement => {
+                "trailing documentation does not follow an element on the same line".to_string()
+            }
+        }
+    }
+}
+
+impl UnattachedDoc {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message: self.reason.message(),
+            ..Default::default()
+        }
+    }
+}
+
+impl SpecFile {
+    /// diagnostics for the file's own documentation, followed by
+    /// the diagnostics of [ParseResult::enumerate_diagnostics].
+    pub fn enumerate_diagnostics(&self, converter: RangeConverter) -> Vec<Diagnostic> {
+        use TagKind::*;
+
+        let file_doc_diagnostics = self
+            .doc
+            .iter()
+            .filter(|tag| ![Title, Notice, Dev].contains(&tag.kind))
+            .map(|tag| Diagnostic {
+                range: converter.to_range(tag.span.clone()),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "this tag is unsupported for file documentation".to_string(),
+                ..Default::default()
+            });
+        let parse_result_diagnostics = ParseResult::diagnostics(
+            &self.elements,
+            &self.unattached_docs,
+            &self.parse_errors,
+            &converter,
+        );
+
+        file_doc_diagnostics
+            .chain(parse_result_diagnostics)
+            .collect()
+    }
+}
+
+impl ParseResult {
+    /// diagnostics for all elements, followed by diagnostics for unattached documentation,
+    /// and then for code that could not be parsed.
+    pub fn enumerate_diagnostics(&self, converter: RangeConverter) -> Vec<Diagnostic> {
+        ParseResult::diagnostics(
+            &self.elements,
+            &self.unattached_docs,
+            &self.parse_errors,
+            &converter,
+        )
+    }
+
+    fn diagnostics(
+        elements: &[CvlElement],
+        unattached_docs: &[UnattachedDoc],
+        parse_errors: &[Span],
+        converter: &RangeConverter,
+    ) -> Vec<Diagnostic> {
+        let element_diagnostics = elements
+            .iter()
+            .flat_map(|element| element.enumerate_diagnostics(converter.clone()));
+        let unattached_diagnostics = unattached_docs
+            .iter()
+            .map(|unattached| unattached.to_diagnostic(converter));
+        let parse_error_diagnostics = parse_errors.iter().map(|span| Diagnostic {
+            range: converter.to_range(span.clone()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: "this code could not be parsed".to_string(),
+            ..Default::default()
+        });
+
+        element_diagnostics
+            .chain(unattached_diagnostics)
+            .chain(parse_error_diagnostics)
+            .collect()
+    }
+}
+
+impl SpecProject {
+    /// every diagnostic of every file in the project, with the path of the file each belongs to.
+    /// for each file, these are in order:
+    /// - the diagnostics of [SpecFile::enumerate_diagnostics], including code that could not be parsed
+    /// - problems with its `import` statements
+    /// - `@inheritdoc` tags and `{@link}`s whose target is not found
+    /// - problems with its `@example` and `@formula` tags
+    /// - uses of deprecated elements
+    /// - the [SpecProject::semantic_problems] found in it
+    /// - its unused declarations, as configured by `config`
+    /// - warnings about the ghosts it declares, or writes in its hooks
+    pub fn diagnostics(&self, config: &UnusedConfig) -> Vec<(&Path, Diagnostic)> {
+        let converters = self
+            .files
+            .iter()
+            .map(|file| file.converter())
+            .collect::<Vec<_>>();
+        let mut diagnostics = vec![Vec::new(); self.files.len()];
+
+        for (index, file) in self.files.iter().enumerate() {
+            let converter = &converters[index];
+            let imported = self.imported_elements(index);
+            let elements = &file.spec.elements;
+            let found = &mut diagnostics[index];
+
+            found.extend(file.spec.enumerate_diagnostics(converter.clone()));
+
+            let mut inheriting = elements.clone();
+            let unresolved = inherit_docs(&mut inheriting, &imported);
+            found.extend(
+                unresolved
+                    .iter()
+                    .map(|unresolved| unresolved.to_diagnostic(converter)),
+            );
+
+            let links = resolve_links(elements, &imported);
+            found.extend(unresolved_link_diagnostics(&links, converter));
+
+            // examples and formulas may refer to the declarations of imported files
+            let context = elements
+                .iter()
+                .chain(imported.iter().copied().flatten())
+                .cloned()
+                .collect::<Vec<_>>();
+            for element in elements {
+                let examples = example_problems(element, &context);
+                found.extend(
+                    examples
+                        .iter()
+                        .map(|problem| problem.to_diagnostic(converter)),
+                );
+                let formulas = formula_problems(element, &context);
+                found.extend(
+                    formulas
+                        .iter()
+                        .map(|problem| problem.to_diagnostic(converter)),
+                );
+            }
+
+            let deprecated = deprecated_uses(elements, &imported);
+            found.extend(deprecated.iter().map(|used| used.to_diagnostic(converter)));
+        }
+
+        for problem in &self.problems {
+            let converter = &converters[problem.file];
+            diagnostics[problem.file].push(problem.to_diagnostic(converter));
+        }
+        for problem in self.semantic_problems() {
+            let converter = &converters[problem.file];
+            diagnostics[problem.file].push(problem.to_diagnostic(converter));
+        }
+        for unused in self.unused_declarations(config) {
+            let converter = &converters[unused.file];
+            diagnostics[unused.file].push(unused.to_diagnostic(converter));
+        }
+
+        let graph = DependencyGraph::for_project(self);
+        for warning in GhostReport::new(&graph).warnings {
+            if let Some(file) = self.file_of_element(warning.element) {
+                diagnostics[file].push(warning.to_diagnostic(&converters[file]));
+            }
+        }
+
+        self.files
+            .iter()
+            .zip(diagnostics)
+            .flat_map(|(file, diagnostics)| {
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| (file.path.as_path(), diagnostic))
+            })
+            .collect()
+    }
+
+    /// the [SpecProject::diagnostics] of a single file of the project
+    pub fn file_diagnostics(&self, path: &Path, config: &UnusedConfig) -> Vec<Diagnostic> {
+        let Some(file) = self.file(path) else {
+            return Vec::new();
+        };
+
+        self.diagnostics(config)
+            .into_iter()
+            .filter(|(diagnostic_path, _)| *diagnostic_path == file.path)
+            .map(|(_, diagnostic)| diagnostic)
+            .collect()
+    }
+
+    /// the index of the file that holds `element`
+    fn file_of_element(&self, element: &CvlElement) -> Option<usize> {
+        self.files.iter().position(|file| {
+            file.spec
+                .elements
+                .as_ptr_range()
+                .contains(&(element as *const _))
+        })
+    }
+}
@@ -0,0 +1,387 @@
+//! `@example` tags holding fenced blocks of CVL code.
+//!
+//! the code of an example is parsed as a spec of its own, so that examples which
+//! no longer parse, or which call names that do not exist, are caught like any other error.
+//! a fence with no language, or with the `cvl` or `spec` language, is checked.
+//! fences of any other language are left alone.
+
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter, Span};
+use crate::{Ast, CvlElement, DocumentationTag, NestedKind, ParseResult, TagKind};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use ropey::Rope;
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests;
+
+const FENCE: &str = "```";
+const CHECKED_LANGUAGES: [&str; 3] = ["", "cvl", "spec"];
+
+/// names that CVL provides, so they need no declaration: keywords that may be followed
+/// by an argument list, builtin functions, and builtin variables and constants.
+/// shared by the checks of examples and of formulas.
+pub(crate) const BUILTINS: [&str; 45] = [
+    "assert",
+    "require",
+    "satisfy",
+    "requireInvariant",
+    "if",
+    "while",
+    "for",
+    "return",
+    "returns",
+    "revert",
+    "rule",
+    "invariant",
+    "function",
+    "definition",
+    "ghost",
+    "hook",
+    "mapping",
+    "preserved",
+    "filtered",
+    "with",
+    "forall",
+    "exists",
+    "sum",
+    "usum",
+    "old",
+    "new",
+    "max",
+    "min",
+    "havoc",
+    "assuming",
+    "keccak256",
+    "sha256",
+    "ecrecover",
+    "to_mathint",
+    "address",
+    "bool",
+    "max_uint",
+    "max_uint8",
+    "max_uint128",
+    "max_uint256",
+    "max_address",
+    "lastReverted",
+    "lastHasThrown",
+    "currentContract",
+    "nativeBalances",
+];
+
+/// casts and checked conversions such as `uint256(x)`, `to_bytes32(x)` and `require_uint8(x)`
+static CONVERSION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(?:require_|assert_)?u?int\d*|(?:to_)?bytes\d*|require_address)$").unwrap()
+});
+
+static METHODS_ENTRY_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"function\s+(?:[\w$]+\.)?([\w$]+)").unwrap());
+
+/// a fenced block of code in an `@example` tag
+#[derive(Debug, Clone, Serialize)]
+pub struct Example {
+    /// the code inside the fence, without the comment markers of the documentation
+    pub code: String,
+    /// the language written after the opening fence, which may be empty
+    pub language: String,
+    /// the span in the source of the opening fence, through the closing fence if there is one
+    pub span: Span,
+    /// whether the closing fence was found
+    pub terminated: bool,
+    /// the code parsed as a spec. only set for CVL examples.
+    pub parsed: Option<ParseResult>,
+    /// for each line of `code`, the span of that line in the source
+    #[serde(skip)]
+    line_spans: Vec<Span>,
+}
+
+impl Example {
+    /// maps a span in `code` to the corresponding span in the source
+    pub fn source_span(&self, span: &Span) -> Span {
+        let rope = Rope::from_str(&self.code);
+        let start = span.start.min(rope.len_chars());
+        let line = rope.char_to_line(start);
+        let column = start - rope.line_to_char(line);
+
+        match self.line_spans.get(line) {
+            Some(line_span) => {
+                let start = (line_span.start + column).min(line_span.end);
+                let end = (start + span.len()).min(line_span.end);
+                start..end
+            }
+            None => self.span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExampleProblemKind {
+    /// the opening fence has no matching closing fence
+    Unterminated,
+    /// part of the code could not be parsed
+    ParseError,
+    /// a call to a name that is not declared in the example, or in the surrounding file
+    UnknownName(String),
+}
+
+/// a problem with the code of an example, located in the source of the documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleProblem {
+    pub kind: ExampleProblemKind,
+    pub span: Span,
+}
+
+impl ExampleProblem {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let (severity, message) = match &self.kind {
+            ExampleProblemKind::Unterminated => (
+                DiagnosticSeverity::ERROR,
+                "example is missing a closing fence".to_string(),
+            ),
+            ExampleProblemKind::ParseError => (
+                DiagnosticSeverity::ERROR,
+                "example failed to parse".to_string(),
+            ),
+            ExampleProblemKind::UnknownName(name) => (
+                DiagnosticSeverity::WARNING,
+                format!("example calls {name}, which is not declared"),
+            ),
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(severity),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// a line of documentation, with the comment markers removed
+struct DocLine<'a> {
+    content: &'a str,
+    span: Span,
+}
+
+/// splits the source of a tag into its lines, removing the comment markers and a single space
+/// after them, so that the indentation of code relative to the markers is kept
+fn doc_lines<'a>(tag: &DocumentationTag, src: &'a str) -> Vec<DocLine<'a>> {
+    let Some(text) = tag.span.byte_slice(src) else {
+        return Vec::new();
+    };
+
+    let mut lines = Vec::new();
+    let mut line_start = tag.span.start;
+
+    for line in text.split_inclusive('\n') {
+        let without_indent = line.trim_start();
+        let without_marker = without_indent.trim_start_matches(['/', '*', '<']);
+        let without_space = without_marker.strip_prefix(' ').unwrap_or(without_marker);
+        let without_end = without_space.trim_end();
+        let content = without_end
+            .strip_suffix("*/")
+            .unwrap_or(without_end)
+            .trim_end();
+
+        let start = line_start + line[..line.len() - without_space.len()].chars().count();
+
+        lines.push(DocLine {
+            content,
+            span: start..start + content.chars().count(),
+        });
+        line_start += line.chars().count();
+    }
+
+    lines
+}
+
+/// the fenced blocks in an `@example` tag
+fn fenced_blocks(tag: &DocumentationTag, src: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut current: Option<(Example, Vec<&str>)> = None;
+
+    for line in doc_lines(tag, src) {
+        let content = if current.is_none() {
+            // the first line still starts with the tag itself
+            line.content
+                .trim_start()
+                .strip_prefix("@example")
+                .unwrap_or(line.content)
+        } else {
+            line.content
+        };
+
+        match current.take() {
+            None => {
+                if let Some(language) = content.trim_start().strip_prefix(FENCE) {
+                    let example = Example {
+                        code: String::new(),
+                        language: language.trim().to_string(),
+                        span: line.span.clone(),
+                        terminated: false,
+                        parsed: None,
+                        line_spans: Vec::new(),
+                    };
+                    current = Some((example, Vec::new()));
+                }
+            }
+            Some((mut example, code_lines)) if content.trim() == FENCE => {
+                example.span.end = line.span.end;
+                example.terminated = true;
+                example.code = code_lines.join("\n");
+                examples.push(example);
+            }
+            Some((mut example, mut code_lines)) => {
+                example.span.end = line.span.end;
+                example.line_spans.push(line.span.clone());
+                code_lines.push(content);
+                current = Some((example, code_lines));
+            }
+        }
+    }
+
+    if let Some((mut example, code_lines)) = current {
+        example.code = code_lines.join("\n");
+        examples.push(example);
+    }
+
+    examples
+}
+
+impl DocumentationTag {
+    /// the fenced code blocks of this tag, if it is an `@example` tag.
+    /// `src` is the source the tag was parsed from. CVL examples are parsed,
+    /// with all documentation allowed to be attached.
+    pub fn examples(&self, src: &str) -> Vec<Example> {
+        if self.kind != TagKind::Example {
+            return Vec::new();
+        }
+
+        fenced_blocks(self, src)
+            .into_iter()
+            .map(|mut example| {
+                if CHECKED_LANGUAGES.contains(&example.language.as_str()) {
+                    example.parsed = Builder::new(&example.code).build_with_diagnostics().ok();
+                }
+                example
+            })
+            .collect()
+    }
+}
+
+impl CvlElement {
+    /// the fenced code blocks in the `@example` tags of this element.
+    /// CVL examples are parsed, with all documentation allowed to be attached.
+    pub fn examples(&self) -> Vec<Example> {
+        self.doc
+            .iter()
+            .flat_map(|tag| tag.examples(&self.src))
+            .collect()
+    }
+}
+
+/// the names of the functions declared in the `methods` blocks of `elements`
+pub(crate) fn methods_entry_names(elements: &[CvlElement]) -> impl Iterator<Item = &str> {
+    elements
+        .iter()
+        .flat_map(|element| &element.nested)
+        .filter(|nested| nested.kind == NestedKind::MethodsEntry)
+        .flat_map(|nested| METHODS_ENTRY_NAME.captures_iter(&nested.text))
+        .filter_map(|captures| captures.get(1))
+        .map(|name| name.as_str())
+}
+
+/// names that may be called by the code in an example
+fn declared_names(elements: &[CvlElement]) -> impl Iterator<Item = &str> {
+    let names = elements.iter().filter_map(|element| element.ast.name());
+    let params = elements
+        .iter()
+        .filter_map(|element| element.ast.params())
+        .flatten()
+        .map(|param| param.name.as_str());
+
+    names.chain(params).chain(methods_entry_names(elements))
+}
+
+/// the problems in the examples of `element`. `context` holds the other elements
+/// that the examples may call, usually the elements of the file `element` is in.
+pub fn example_problems(element: &CvlElement, context: &[CvlElement]) -> Vec<ExampleProblem> {
+    let mut problems = Vec::new();
+
+    for example in element.examples() {
+        if !example.terminated {
+            problems.push(ExampleProblem {
+                kind: ExampleProblemKind::Unterminated,
+                span: example.span.clone(),
+            });
+        }
+
+        let Some(parsed) = &example.parsed else {
+            continue;
+        };
+
+        for span in &parsed.parse_errors {
+            problems.push(ExampleProblem {
+                kind: ExampleProblemKind::ParseError,
+                span: example.source_span(span),
+            });
+        }
+
+        let references = parsed
+            .elements
+            .iter()
+            .flat_map(|parsed_element| {
+                let references = parsed_element.references();
+                // the header of a hook binds names before an argument list, as in `uint v (uint old)`
+                let body_start = match parsed_element.ast {
+                    Ast::HookSload { .. }
+                    | Ast::HookSstore { .. }
+                    | Ast::HookCreate { .. }
+                    | Ast::HookOpcode { .. } => example
+                        .code
+                        .chars()
+                        .skip(parsed_element.element_span.start)
+                        .position(|ch| ch == '{')
+                        .map_or(usize::MAX, |i| parsed_element.element_span.start + i),
+                    _ => 0,
+                };
+                references
+                    .into_iter()
+                    .filter(move |reference| reference.span.start >= body_start)
+            })
+            .collect::<Vec<_>>();
+
+        // any name that is also used outside of a call, such as a local `method f;`, is declared
+        let mut known: HashSet<&str> = BUILTINS.into_iter().collect();
+        known.extend(declared_names(&parsed.elements));
+        known.extend(declared_names(context));
+        known.extend(declared_names(std::slice::from_ref(element)));
+        known.extend(
+            references
+                .iter()
+                .filter(|reference| !reference.is_call)
+                .map(|reference| reference.name.as_str()),
+        );
+
+        for reference in &references {
+            let name = reference.name.as_str();
+            // calls through a contract alias, such as `token.balanceOf(e)`, are not checked
+            if !reference.is_call || name.contains('.') {
+                continue;
+            }
+            if known.contains(name) || CONVERSION.is_match(name) {
+                continue;
+            }
+
+            problems.push(ExampleProblem {
+                kind: ExampleProblemKind::UnknownName(name.to_string()),
+                span: example.source_span(&reference.span),
+            });
+        }
+    }
+
+    problems
+}
@@ -0,0 +1,108 @@
+use super::{example_problems, ExampleProblemKind};
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use indoc::indoc;
+use lsp_types::DiagnosticSeverity;
+use ropey::Rope;
+
+#[test]
+fn examples_are_parsed() {
+    let src = indoc! {"
+        /// @notice the fee of an amount
+        /// @example
+        /// ```cvl
+        /// rule feeIsSmall(uint x) {
+        ///     assert fee(x) <= x;
+        /// }
+        /// ```
+        /// @example
+        /// ```solidity
+        /// uint y = fee(5);
+        /// ```
+        definition fee(uint x) returns uint = x / 100;
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let examples = elements[0].examples();
+
+    assert_eq!(examples.len(), 2);
+
+    let cvl = &examples[0];
+    assert_eq!(cvl.language, "cvl");
+    assert!(cvl.terminated);
+    assert_eq!(
+        cvl.code,
+        "rule feeIsSmall(uint x) {\n    assert fee(x) <= x;\n}"
+    );
+    let parsed = cvl.parsed.as_ref().unwrap();
+    assert_eq!(parsed.elements[0].ast.name(), Some("feeIsSmall"));
+    assert!(parsed.parse_errors.is_empty());
+
+    let solidity = &examples[1];
+    assert_eq!(solidity.language, "solidity");
+    assert!(solidity.parsed.is_none());
+
+    let json = serde_json::to_value(solidity).unwrap();
+    assert_eq!(json["code"], "uint y = fee(5);");
+    assert_eq!(json["parsed"], serde_json::Value::Null);
+    assert!(json.get("line_spans").is_none());
+
+    assert!(example_problems(&elements[0], &elements).is_empty());
+}
+
+#[test]
+fn example_problems_are_reported_at_their_lines() {
+    let src = indoc! {"
+        /**
+         * @example
+         * ```
+         * rule usesMissing(env e) {
+         *     method f; calldataarg args;
+         *     f(e, args);
+         *     assert missing(e) == helper(e);
+         * }
+         * ```
+         */
+        function helper(env e) returns uint { return 1; }
+
+        /// @example
+        /// ```
+        /// rule broken {
+        /// }
+        /// }
+        definition unterminated() returns bool = true;
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let converter = RangeConverter::new(Rope::from_str(src));
+
+    let starred = &elements[0].examples()[0];
+    assert!(starred
+        .code
+        .starts_with("rule usesMissing(env e) {\n    method f; calldataarg args;\n"));
+
+    let problems = example_problems(&elements[0], &elements);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].kind,
+        ExampleProblemKind::UnknownName("missing".to_string())
+    );
+    assert_eq!(problems[0].span.byte_slice(src), Some("missing"));
+
+    let diagnostic = problems[0].to_diagnostic(&converter);
+    assert_eq!(diagnostic.range.start.line, 6);
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+
+    let problems = example_problems(&elements[1], &elements);
+    let kinds = problems
+        .iter()
+        .map(|problem| &problem.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            &ExampleProblemKind::Unterminated,
+            &ExampleProblemKind::ParseError
+        ]
+    );
+    assert_eq!(problems[1].span.byte_slice(src), Some("}"));
+    assert_eq!(converter.to_range(problems[1].span.clone()).start.line, 16);
+}
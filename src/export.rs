@@ -0,0 +1,431 @@
+//! renders the documentation of a set of files as Markdown, HTML or JSON.
+
+use crate::id::{element_ids, slugify, ElementId};
+use crate::inherit::{inherit_docs, inherited_from};
+use crate::links::{resolve_links, Segment};
+use crate::outline::{outline, Section};
+use crate::{Ast, CvlElement, DocumentationTag, TagKind};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// a piece of text within a block
+enum Inline {
+    Text(String),
+    /// a `{@link target}`, with the anchor of its target if it was resolved
+    Link {
+        target: String,
+        anchor: Option<String>,
+    },
+}
+
+/// a piece of a rendered document, independent of the output format
+enum Block {
+    /// a heading, with the anchor of the element it documents
+    Heading(usize, String, Option<String>),
+    /// code, with its language
+    Code(String, String),
+    Paragraph(Vec<Inline>),
+    /// a labelled field, such as the reason an element is deprecated
+    Field(&'static str, Vec<Inline>),
+    /// named items, such as parameters, with their descriptions
+    List(&'static str, Vec<(String, Vec<Inline>)>),
+}
+
+/// the anchor of an element, unique among all the files of the document
+fn anchor(id: &ElementId) -> String {
+    slugify(&format!("{} {}", id.path, id.slug()))
+}
+
+/// the exported files, the identifiers of their elements, and where their links point to
+struct Document<'a> {
+    files: &'a [(&'a str, &'a [CvlElement])],
+    ids: Vec<Vec<ElementId>>,
+    /// for each file, the anchors of the targets of its resolved links, by target
+    link_anchors: Vec<HashMap<String, String>>,
+}
+
+impl<'a> Document<'a> {
+    fn new(files: &'a [(&'a str, &'a [CvlElement])]) -> Document<'a> {
+        let ids = files
+            .iter()
+            .map(|&(path, elements)| element_ids(path, elements))
+            .collect::<Vec<_>>();
+
+        let mut document = Document {
+            files,
+            ids,
+            link_anchors: Vec::new(),
+        };
+
+        document.link_anchors = (0..files.len())
+            .map(|file| {
+                resolve_links(files[file].1, &document.others(file))
+                    .into_iter()
+                    .filter_map(|resolved| {
+                        let anchor = document.anchor_of(resolved.target.element()?)?;
+                        Some((resolved.link.target, anchor))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        document
+    }
+
+    /// the elements of every file other than `file`
+    fn others(&self, file: usize) -> Vec<&'a [CvlElement]> {
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != file)
+            .map(|(_, &(_, elements))| elements)
+            .collect()
+    }
+
+    /// the file of an element of [Document::files], and its index in that file
+    fn position(&self, element: &CvlElement) -> Option<(usize, usize)> {
+        self.files
+            .iter()
+            .enumerate()
+            .find_map(|(file, (_, elements))| {
+                let index = elements.iter().position(|e| std::ptr::eq(e, element))?;
+                Some((file, index))
+            })
+    }
+
+    fn anchor_of(&self, element: &CvlElement) -> Option<String> {
+        let (file, index) = self.position(element)?;
+        Some(anchor(&self.ids[file][index]))
+    }
+
+    /// the element of `file` that `element`, a copy of it, was made from
+    fn original(&self, file: usize, element: &CvlElement) -> Option<&'a CvlElement> {
+        self.files[file]
+            .1
+            .iter()
+            .find(|original| original.element_span == element.element_span)
+    }
+
+    /// the documentation of `element`, which is a copy with inherited documentation of an
+    /// element of `file`. each tag is given with the element whose source it was parsed from.
+    fn documentation(
+        &self,
+        file: usize,
+        element: &CvlElement,
+    ) -> Vec<(&'a DocumentationTag, &'a CvlElement)> {
+        let Some(original) = self.original(file, element) else {
+            return Vec::new();
+        };
+        let source = inherited_from(original, &self.others(file));
+
+        element
+            .doc
+            .iter()
+            .filter_map(|tag| {
+                if let Some(local) = original.doc.iter().find(|local| *local == tag) {
+                    return Some((local, original));
+                }
+                let source = source?;
+                let inherited = source.doc.iter().find(|inherited| {
+                    inherited.kind == tag.kind && inherited.description == tag.description
+                })?;
+                Some((inherited, source))
+            })
+            .collect()
+    }
+
+    /// the description of a tag, with its links
+    fn text(&self, tag: &DocumentationTag, origin: &CvlElement) -> Vec<Inline> {
+        let anchors = self
+            .position(origin)
+            .map(|(file, _)| &self.link_anchors[file]);
+
+        let mut text = tag
+            .segments(&origin.src)
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => Inline::Text(text),
+                Segment::Link(link) => Inline::Link {
+                    anchor: anchors.and_then(|anchors| anchors.get(&link.target).cloned()),
+                    target: link.target,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(Inline::Text(first)) = text.first_mut() {
+            *first = first.trim_start().to_string();
+        }
+        if let Some(Inline::Text(last)) = text.last_mut() {
+            *last = last.trim_end().to_string();
+        }
+
+        text
+    }
+
+    fn element_blocks(
+        &self,
+        file: usize,
+        element: &CvlElement,
+        level: usize,
+        blocks: &mut Vec<Block>,
+    ) {
+        match &element.ast {
+            Ast::FreeFormComment { text } => {
+                blocks.push(Block::Paragraph(vec![Inline::Text(
+                    text.trim().to_string(),
+                )]));
+                return;
+            }
+            Ast::Import { .. } => return,
+            _ => {}
+        }
+
+        let title = element.title().unwrap_or_else(|| element.ast.to_string());
+        let anchor = self
+            .original(file, element)
+            .and_then(|original| self.anchor_of(original));
+        blocks.push(Block::Heading(level, title, anchor));
+        blocks.push(Block::Code("cvl".to_string(), element.ast.signature()));
+
+        let mut params = Vec::new();
+        let mut requirements = Vec::new();
+
+        for (tag, origin) in self.documentation(file, element) {
+            let text = || self.text(tag, origin);
+            match tag.kind {
+                TagKind::Title | TagKind::InheritDoc => {}
+                TagKind::Notice => blocks.push(Block::Paragraph(text())),
+                TagKind::Dev => blocks.push(Block::Field("Dev", text())),
+                TagKind::Param => {
+                    let mut text = text();
+                    let name = match text.first_mut() {
+                        Some(Inline::Text(first)) => {
+                            let (name, rest) = first
+                                .split_once(char::is_whitespace)
+                                .unwrap_or((first.as_str(), ""));
+                            let name = name.to_string();
+                            *first = rest.trim_start().to_string();
+                            name
+                        }
+                        _ => String::new(),
+                    };
+                    params.push((name, text));
+                }
+                TagKind::Return => blocks.push(Block::Field("Returns", text())),
+                TagKind::Formula => blocks.push(Block::Field("Formula", text())),
+                TagKind::Deprecated => blocks.push(Block::Field("Deprecated", text())),
+                TagKind::Since => blocks.push(Block::Field("Since", text())),
+                TagKind::Example => {
+                    for example in tag.examples(&origin.src) {
+                        blocks.push(Block::Code(example.language, example.code));
+                    }
+                }
+                TagKind::Req => requirements.push((tag.description.trim().to_string(), Vec::new())),
+            }
+        }
+
+        if !params.is_empty() {
+            blocks.push(Block::List("Parameters", params));
+        }
+        if !requirements.is_empty() {
+            blocks.push(Block::List("Requirements", requirements));
+        }
+    }
+
+    fn section_blocks(
+        &self,
+        file: usize,
+        section: &Section,
+        level: usize,
+        blocks: &mut Vec<Block>,
+    ) {
+        if section.level > 0 {
+            blocks.push(Block::Heading(level, section.title.clone(), None));
+            if !section.text.is_empty() {
+                blocks.push(Block::Paragraph(vec![Inline::Text(section.text.clone())]));
+            }
+        }
+
+        let nested = if section.level > 0 { level + 1 } else { level };
+        for element in &section.elements {
+            self.element_blocks(file, element, nested.min(6), blocks);
+        }
+        for subsection in &section.subsections {
+            self.section_blocks(file, subsection, nested.min(6), blocks);
+        }
+    }
+
+    /// the blocks of each file: a heading with its path, followed by its sections and elements.
+    /// elements inherit documentation from the other files.
+    fn blocks(&self) -> Vec<Block> {
+        let mut blocks = Vec::new();
+
+        for (file, &(path, elements)) in self.files.iter().enumerate() {
+            let mut elements = elements.to_vec();
+            inherit_docs(&mut elements, &self.others(file));
+
+            blocks.push(Block::Heading(1, path.to_string(), None));
+            self.section_blocks(file, &outline(&elements), 2, &mut blocks);
+        }
+
+        blocks
+    }
+}
+
+fn blocks(files: &[(&str, &[CvlElement])]) -> Vec<Block> {
+    Document::new(files).blocks()
+}
+
+fn markdown_text(text: &[Inline]) -> String {
+    text.iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => text.clone(),
+            Inline::Link {
+                target,
+                anchor: Some(anchor),
+            } => format!("[`{target}`](#{anchor})"),
+            Inline::Link { target, .. } => format!("`{target}`"),
+        })
+        .collect()
+}
+
+/// the documentation of each file, given with its path, as Markdown.
+/// the sections of the file, given by the headings in its freeform comments, are kept.
+/// `import` statements are left out.
+pub fn to_markdown(files: &[(&str, &[CvlElement])]) -> String {
+    let mut markdown = String::new();
+
+    for block in blocks(files) {
+        match block {
+            Block::Heading(level, title, anchor) => {
+                if let Some(anchor) = anchor {
+                    writeln!(markdown, "<a id=\"{anchor}\"></a>\n").unwrap();
+                }
+                writeln!(markdown, "{} {title}\n", "#".repeat(level)).unwrap()
+            }
+            Block::Code(language, code) => {
+                writeln!(markdown, "```{language}\n{code}\n```\n").unwrap()
+            }
+            Block::Paragraph(text) => writeln!(markdown, "{}\n", markdown_text(&text)).unwrap(),
+            Block::Field(label, text) => {
+                writeln!(markdown, "**{label}:** {}\n", markdown_text(&text)).unwrap()
+            }
+            Block::List(label, items) => {
+                writeln!(markdown, "**{label}:**\n").unwrap();
+                for (name, description) in items {
+                    let description = markdown_text(&description);
+                    if description.is_empty() {
+                        writeln!(markdown, "- `{name}`").unwrap();
+                    } else {
+                        writeln!(markdown, "- `{name}`: {description}").unwrap();
+                    }
+                }
+                markdown.push('\n');
+            }
+        }
+    }
+
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_text(text: &[Inline]) -> String {
+    text.iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => escape_html(text),
+            Inline::Link {
+                target,
+                anchor: Some(anchor),
+            } => format!(
+                "<a href=\"#{anchor}\"><code>{}</code></a>",
+                escape_html(target)
+            ),
+            Inline::Link { target, .. } => format!("<code>{}</code>", escape_html(target)),
+        })
+        .collect()
+}
+
+/// the documentation of each file, given with its path, as a stand-alone HTML page
+pub fn to_html(files: &[(&str, &[CvlElement])]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>CVLDoc</title>\n</head>\n<body>\n",
+    );
+
+    for block in blocks(files) {
+        match block {
+            Block::Heading(level, title, anchor) => {
+                let id = anchor
+                    .map(|anchor| format!(" id=\"{anchor}\""))
+                    .unwrap_or_default();
+                writeln!(html, "<h{level}{id}>{}</h{level}>", escape_html(&title)).unwrap()
+            }
+            Block::Code(language, code) => {
+                let class = if language.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape_html(&language))
+                };
+                writeln!(
+                    html,
+                    "<pre><code{class}>{}</code></pre>",
+                    escape_html(&code)
+                )
+                .unwrap()
+            }
+            Block::Paragraph(text) => writeln!(html, "<p>{}</p>", html_text(&text)).unwrap(),
+            Block::Field(label, text) => writeln!(
+                html,
+                "<p><strong>{label}:</strong> {}</p>",
+                html_text(&text)
+            )
+            .unwrap(),
+            Block::List(label, items) => {
+                writeln!(html, "<p><strong>{label}:</strong></p>\n<ul>").unwrap();
+                for (name, description) in items {
+                    let name = escape_html(&name);
+                    let description = html_text(&description);
+                    if description.is_empty() {
+                        writeln!(html, "<li><code>{name}</code></li>").unwrap();
+                    } else {
+                        writeln!(html, "<li><code>{name}</code>: {description}</li>").unwrap();
+                    }
+                }
+                html.push_str("</ul>\n");
+            }
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[derive(Serialize)]
+struct FileOutline<'a> {
+    path: &'a str,
+    outline: Section,
+}
+
+/// the outline of each file, given with its path, as JSON
+pub fn to_json(files: &[(&str, &[CvlElement])]) -> String {
+    let outlines = files
+        .iter()
+        .map(|&(path, elements)| FileOutline {
+            path,
+            outline: outline(elements),
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&outlines).expect("outlines are serializable")
+}
@@ -0,0 +1,166 @@
+use super::{to_html, to_json, to_markdown};
+use crate::parse::builder::Builder;
+use indoc::indoc;
+
+const SRC: &str = indoc! {"
+    /// @title Maximal balance
+    /// @notice balances never exceed this
+    definition MAX() returns uint = 1000;
+
+    ////////////////////////////////
+    //// # Rules
+    //// rules about transfers
+    ////////////////////////////////
+
+    /// @title transfers preserve the total supply
+    /// @param a the sender
+    /// @param b the recipient
+    /// @dev slow to verify
+    /// @req REQ-1
+    rule transfer(address a, address b) { assert true; }
+"};
+
+#[test]
+fn markdown_keeps_sections() {
+    let elements = Builder::new(SRC).build().unwrap();
+    let markdown = to_markdown(&[("token.spec", &elements)]);
+
+    let expected = indoc! {"
+        # token.spec
+
+        <a id=\"token-spec-definition-MAX\"></a>
+
+        ## Maximal balance
+
+        ```cvl
+        definition MAX() returns uint
+        ```
+
+        balances never exceed this
+
+        ## Rules
+
+        rules about transfers
+
+        <a id=\"token-spec-rule-transfer\"></a>
+
+        ### transfers preserve the total supply
+
+        ```cvl
+        rule transfer(address a, address b)
+        ```
+
+        **Dev:** slow to verify
+
+        **Parameters:**
+
+        - `a`: the sender
+        - `b`: the recipient
+
+        **Requirements:**
+
+        - `REQ-1`
+    "};
+    assert_eq!(markdown, expected);
+}
+
+#[test]
+fn html_and_json() {
+    let elements = Builder::new(SRC).build().unwrap();
+
+    let html = to_html(&[("token.spec", &elements)]);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(
+        "<h3 id=\"token-spec-rule-transfer\">transfers preserve the total supply</h3>\n"
+    ));
+    assert!(html.contains("<li><code>a</code>: the sender</li>\n"));
+    assert!(html.ends_with("</body>\n</html>\n"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&to_json(&[("token.spec", &elements)])).unwrap();
+    assert_eq!(json[0]["path"], "token.spec");
+    assert_eq!(json[0]["outline"]["subsections"][0]["title"], "Rules");
+}
+
+#[test]
+fn examples_links_and_inherited_documentation() {
+    let base = indoc! {"
+        /// @notice computes the fee, see {@link MAX}
+        /// @example
+        /// ```cvl
+        /// rule feeIsSmall(uint x) {
+        ///     assert fee(x) <= x;
+        /// }
+        /// ```
+        function fee(uint x) returns uint { return 0; }
+    "};
+    let src = indoc! {"
+        /// @inheritdoc
+        /// @dev relies on {@link
+        /// MAX} but not on {@link missing}
+        function fee(uint x) returns uint { return 1; }
+    "};
+    let base_elements = Builder::new(base).build().unwrap();
+    let elements = Builder::new(src).build().unwrap();
+    let mut token_elements = Builder::new(SRC).build().unwrap();
+    token_elements.truncate(1);
+    let files = [
+        ("token.spec", token_elements.as_slice()),
+        ("base.spec", base_elements.as_slice()),
+        ("harness.spec", elements.as_slice()),
+    ];
+
+    let markdown = to_markdown(&files);
+    let harness = &markdown[markdown.find("# harness.spec").unwrap()..];
+    let expected = indoc! {"
+        # harness.spec
+
+        <a id=\"harness-spec-function-fee-uint\"></a>
+
+        ## fee
+
+        ```cvl
+        function fee(uint x) returns uint
+        ```
+
+        computes the fee, see [`MAX`](#token-spec-definition-MAX)
+
+        ```cvl
+        rule feeIsSmall(uint x) {
+            assert fee(x) <= x;
+        }
+        ```
+
+        **Dev:** relies on [`MAX`](#token-spec-definition-MAX) but not on `missing`
+    "};
+    assert_eq!(harness, expected);
+
+    let html = to_html(&files);
+    assert!(html.contains(
+        "<p>computes the fee, see <a href=\"#token-spec-definition-MAX\"><code>MAX</code></a></p>\n"
+    ));
+    assert!(html.contains("<pre><code class=\"language-cvl\">rule feeIsSmall(uint x) {\n    assert fee(x) &lt;= x;\n}</code></pre>\n"));
+    assert!(html.contains("but not on <code>missing</code></p>\n"));
+}
+
+#[test]
+fn deprecation_notices() {
+    let src = indoc! {"
+        /// @notice the old fee
+        /// @deprecated use {@link fee} instead
+        /// @since 1.2
+        definition oldFee(uint x) returns uint = x / 100;
+
+        definition fee(uint x) returns uint = x / 10;
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let markdown = to_markdown(&[("fees.spec", &elements)]);
+
+    assert!(markdown.contains(indoc! {"
+        the old fee
+
+        **Deprecated:** use [`fee`](#fees-spec-definition-fee) instead
+
+        **Since:** 1.2
+    "}));
+}
@@ -0,0 +1,858 @@
+//! `@formula` tags, written either as a CVL expression or as LaTeX.
+//!
+//! a formula is LaTeX when it is wrapped in `$...$`, `$$...$$`, `\(...\)` or `\[...\]`,
+//! or when it contains a LaTeX command such as `\forall`. any other formula is parsed
+//! as a CVL expression, which can be rendered as LaTeX or MathML.
+
+use crate::examples::{methods_entry_names, BUILTINS};
+use crate::util::{ByteSpan, RangeConverter, Span};
+use crate::{Ast, CvlElement, DocumentationTag, TagKind};
+use chumsky::prelude::*;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+static LATEX_COMMAND: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\[A-Za-z]+").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Formula {
+    Cvl(Expr),
+    /// the LaTeX source, without its delimiters
+    Latex(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Quantifier {
+    Forall,
+    Exists,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BinaryOp {
+    Iff,
+    Implies,
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
+
+/// a CVL expression. spans are in chars, relative to the start of the formula.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Expr {
+    /// a name, which may contain dots, as in `e.msg.sender`
+    Ident {
+        name: String,
+        span: Span,
+    },
+    Number(String),
+    Bool(bool),
+    Call {
+        name: String,
+        span: Span,
+        args: Vec<Expr>,
+    },
+    Index {
+        base: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Ternary {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    Quantified {
+        quantifier: Quantifier,
+        ty: String,
+        var: String,
+        body: Box<Expr>,
+    },
+}
+
+/// a formula that is neither LaTeX nor a valid CVL expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaError {
+    /// the span of the error, relative to the start of the formula
+    pub span: Span,
+    pub message: String,
+}
+
+impl BinaryOp {
+    const ALL: [BinaryOp; 16] = [
+        BinaryOp::Iff,
+        BinaryOp::Implies,
+        BinaryOp::Or,
+        BinaryOp::And,
+        BinaryOp::Eq,
+        BinaryOp::Ne,
+        BinaryOp::Le,
+        BinaryOp::Ge,
+        BinaryOp::Lt,
+        BinaryOp::Gt,
+        BinaryOp::Pow,
+        BinaryOp::Add,
+        BinaryOp::Sub,
+        BinaryOp::Mul,
+        BinaryOp::Div,
+        BinaryOp::Mod,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BinaryOp::Iff => "<=>",
+            BinaryOp::Implies => "=>",
+            BinaryOp::Or => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Pow => "**",
+        }
+    }
+
+    /// higher binds tighter
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Iff => 1,
+            BinaryOp::Implies => 2,
+            BinaryOp::Or => 3,
+            BinaryOp::And => 4,
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 5,
+            BinaryOp::Add | BinaryOp::Sub => 6,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 7,
+            BinaryOp::Pow => 9,
+        }
+    }
+
+    fn latex(self) -> &'static str {
+        match self {
+            BinaryOp::Iff => r"\iff",
+            BinaryOp::Implies => r"\implies",
+            BinaryOp::Or => r"\lor",
+            BinaryOp::And => r"\land",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => r"\neq",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => r"\leq",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => r"\geq",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => r"\cdot",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => r"\bmod",
+            BinaryOp::Pow => "^",
+        }
+    }
+
+    fn mathml(self) -> &'static str {
+        match self {
+            BinaryOp::Iff => "&#x21D4;",
+            BinaryOp::Implies => "&#x21D2;",
+            BinaryOp::Or => "&#x2228;",
+            BinaryOp::And => "&#x2227;",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "&#x2260;",
+            BinaryOp::Lt => "&lt;",
+            BinaryOp::Le => "&#x2264;",
+            BinaryOp::Gt => "&gt;",
+            BinaryOp::Ge => "&#x2265;",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "&#x22C5;",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "mod",
+            BinaryOp::Pow => "^",
+        }
+    }
+}
+
+const TERNARY_PRECEDENCE: u8 = 0;
+const UNARY_PRECEDENCE: u8 = 8;
+const ATOM_PRECEDENCE: u8 = 10;
+
+fn expr_parser() -> impl Parser<char, Expr, Error = Simple<char>> {
+    let binary = |lhs: Expr, (op, rhs): (BinaryOp, Expr)| Expr::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    };
+
+    // operators are matched by their longest spelling first, so that `<=>` is not read as `<=`
+    let operator = |ops: &'static [BinaryOp]| {
+        choice(BinaryOp::ALL.map(|op| just(op.as_str()).to(op)))
+            .try_map(move |op, span| {
+                if ops.contains(&op) {
+                    Ok(op)
+                } else {
+                    Err(Simple::custom(span, "unexpected operator"))
+                }
+            })
+            .padded()
+    };
+
+    recursive(|expr| {
+        let name = text::ident()
+            .then(just('.').ignore_then(text::ident()).repeated())
+            .map(|(head, rest)| {
+                std::iter::once(head)
+                    .chain(rest)
+                    .collect::<Vec<_>>()
+                    .join(".")
+            });
+
+        let args = expr
+            .clone()
+            .separated_by(just(',').padded())
+            .delimited_by(just('(').padded(), just(')').padded());
+
+        let call_or_ident = name
+            .map_with_span(|name, span| (name, span))
+            .then(args.or_not())
+            .map(|((name, span), args)| match (name.as_str(), args) {
+                ("true", None) => Expr::Bool(true),
+                ("false", None) => Expr::Bool(false),
+                (_, Some(args)) => Expr::Call { name, span, args },
+                (_, None) => Expr::Ident { name, span },
+            });
+
+        let number = filter(|c: &char| c.is_ascii_digit())
+            .chain(filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_').repeated())
+            .collect::<String>()
+            .map(Expr::Number);
+
+        let quantified = text::keyword("forall")
+            .to(Quantifier::Forall)
+            .or(text::keyword("exists").to(Quantifier::Exists))
+            .then(text::ident().padded())
+            .then(text::ident().padded())
+            .then_ignore(just('.'))
+            .then(expr.clone())
+            .map(|(((quantifier, ty), var), body)| Expr::Quantified {
+                quantifier,
+                ty,
+                var,
+                body: Box::new(body),
+            });
+
+        let parenthesized = expr
+            .clone()
+            .delimited_by(just('(').padded(), just(')').padded());
+
+        let atom = choice((quantified, number, call_or_ident, parenthesized)).padded();
+
+        let indexed = atom
+            .then(
+                expr.clone()
+                    .delimited_by(just('[').padded(), just(']').padded())
+                    .repeated(),
+            )
+            .foldl(|base, index| Expr::Index {
+                base: Box::new(base),
+                index: Box::new(index),
+            });
+
+        // as in mathematical notation, `**` binds tighter than a prefix operator on its left,
+        // so `-x ** 2` is `-(x ** 2)`. it associates to the right, and allows a prefix operator
+        // on its right, as in `2 ** -n`.
+        let unary = recursive(|unary| {
+            let power = indexed
+                .then(operator(&[BinaryOp::Pow]).then(unary.clone()).or_not())
+                .map(move |(lhs, rhs)| match rhs {
+                    Some(rhs) => binary(lhs, rhs),
+                    None => lhs,
+                });
+
+            just('!')
+                .to(UnaryOp::Not)
+                .or(just('-').to(UnaryOp::Neg))
+                .padded()
+                .then(unary)
+                .map(|(op, operand)| Expr::Unary {
+                    op,
+                    operand: Box::new(operand),
+                })
+                .or(power)
+        })
+        .boxed();
+
+        let product = unary
+            .clone()
+            .then(
+                operator(&[BinaryOp::Mul, BinaryOp::Div, BinaryOp::Mod])
+                    .then(unary)
+                    .repeated(),
+            )
+            .foldl(binary)
+            .boxed();
+        let sum = product
+            .clone()
+            .then(
+                operator(&[BinaryOp::Add, BinaryOp::Sub])
+                    .then(product)
+                    .repeated(),
+            )
+            .foldl(binary)
+            .boxed();
+        let comparisons = &[
+            BinaryOp::Eq,
+            BinaryOp::Ne,
+            BinaryOp::Lt,
+            BinaryOp::Le,
+            BinaryOp::Gt,
+            BinaryOp::Ge,
+        ];
+        let comparison = sum
+            .clone()
+            .then(operator(comparisons).then(sum).repeated())
+            .foldl(binary)
+            .boxed();
+        let and = comparison
+            .clone()
+            .then(operator(&[BinaryOp::And]).then(comparison).repeated())
+            .foldl(binary)
+            .boxed();
+        let or = and
+            .clone()
+            .then(operator(&[BinaryOp::Or]).then(and).repeated())
+            .foldl(binary)
+            .boxed();
+
+        // implication associates to the right
+        let implies = recursive(|implies| {
+            or.clone()
+                .then(operator(&[BinaryOp::Implies]).then(implies).or_not())
+                .map(move |(lhs, rhs)| match rhs {
+                    Some(rhs) => binary(lhs, rhs),
+                    None => lhs,
+                })
+        })
+        .boxed();
+        let iff = implies
+            .clone()
+            .then(operator(&[BinaryOp::Iff]).then(implies).repeated())
+            .foldl(binary)
+            .boxed();
+
+        iff.clone()
+            .then(
+                just('?')
+                    .padded()
+                    .ignore_then(expr.clone())
+                    .then_ignore(just(':').padded())
+                    .then(expr)
+                    .or_not(),
+            )
+            .map(|(condition, branches)| match branches {
+                Some((then, otherwise)) => Expr::Ternary {
+                    condition: Box::new(condition),
+                    then: Box::new(then),
+                    otherwise: Box::new(otherwise),
+                },
+                None => condition,
+            })
+    })
+}
+
+/// strips the delimiters of a LaTeX formula, or returns `None` if the formula is not LaTeX
+fn latex_body(text: &str) -> Option<&str> {
+    let delimiters = [("$$", "$$"), ("$", "$"), (r"\(", r"\)"), (r"\[", r"\]")];
+
+    let delimited = delimiters.iter().find_map(|(open, close)| {
+        text.strip_prefix(open)?
+            .strip_suffix(close)
+            .filter(|body| !body.is_empty())
+    });
+
+    match delimited {
+        Some(body) => Some(body.trim()),
+        None if LATEX_COMMAND.is_match(text) => Some(text),
+        None => None,
+    }
+}
+
+pub fn parse_formula(text: &str) -> Result<Formula, FormulaError> {
+    let trimmed = text.trim();
+    if let Some(latex) = latex_body(trimmed) {
+        return Ok(Formula::Latex(latex.to_string()));
+    }
+
+    expr_parser()
+        .padded()
+        .then_ignore(end())
+        .parse(text)
+        .map(Formula::Cvl)
+        .map_err(|errors| {
+            let error = errors
+                .into_iter()
+                .max_by_key(|error| error.span().start)
+                .expect("a failed parse has at least one error");
+
+            let message = match error.found() {
+                Some(found) => format!("formula is not a CVL expression: unexpected {found:?}"),
+                None => "formula is not a CVL expression: unexpected end of formula".to_string(),
+            };
+            FormulaError {
+                span: error.span(),
+                message,
+            }
+        })
+}
+
+impl DocumentationTag {
+    /// the parsed formula, if this is a `@formula` tag
+    pub fn formula(&self) -> Option<Result<Formula, FormulaError>> {
+        match self.kind {
+            TagKind::Formula => Some(parse_formula(&self.description)),
+            _ => None,
+        }
+    }
+}
+
+fn escape_latex(name: &str) -> String {
+    name.replace('_', r"\_").replace('$', r"\$")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn latex_ident(name: &str) -> String {
+    if name.chars().count() == 1 {
+        name.to_string()
+    } else {
+        format!(r"\mathit{{{}}}", escape_latex(name))
+    }
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Ternary { .. } | Expr::Quantified { .. } => TERNARY_PRECEDENCE,
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Unary { .. } => UNARY_PRECEDENCE,
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+
+    /// the precedences a child must exceed to avoid parentheses, on the left and on the right
+    fn binary_child_precedences(op: BinaryOp) -> (u8, u8) {
+        let precedence = op.precedence();
+        match op {
+            // right associative
+            BinaryOp::Implies | BinaryOp::Pow => (precedence + 1, precedence),
+            _ => (precedence, precedence + 1),
+        }
+    }
+
+    fn write_latex(&self, out: &mut String, min_precedence: u8) {
+        let parenthesize = self.precedence() < min_precedence;
+        if parenthesize {
+            out.push_str(r"\left(");
+        }
+
+        match self {
+            Expr::Ident { name, .. } => out.push_str(&latex_ident(name)),
+            Expr::Number(number) => out.push_str(number),
+            Expr::Bool(value) => write!(out, r"\mathrm{{{value}}}").unwrap(),
+            Expr::Call { name, args, .. } => {
+                write!(out, r"\mathrm{{{}}}(", escape_latex(name)).unwrap();
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    arg.write_latex(out, TERNARY_PRECEDENCE);
+                }
+                out.push(')');
+            }
+            Expr::Index { base, index } => {
+                base.write_latex(out, ATOM_PRECEDENCE);
+                out.push('[');
+                index.write_latex(out, TERNARY_PRECEDENCE);
+                out.push(']');
+            }
+            Expr::Unary { op, operand } => {
+                out.push_str(match op {
+                    UnaryOp::Not => r"\lnot ",
+                    UnaryOp::Neg => "-",
+                });
+                operand.write_latex(out, UNARY_PRECEDENCE);
+            }
+            Expr::Binary {
+                op: BinaryOp::Pow,
+                lhs,
+                rhs,
+            } => {
+                lhs.write_latex(out, ATOM_PRECEDENCE);
+                out.push_str("^{");
+                rhs.write_latex(out, TERNARY_PRECEDENCE);
+                out.push('}');
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let (left, right) = Expr::binary_child_precedences(*op);
+                lhs.write_latex(out, left);
+                write!(out, " {} ", op.latex()).unwrap();
+                rhs.write_latex(out, right);
+            }
+            Expr::Ternary {
+                condition,
+                then,
+                otherwise,
+            } => {
+                out.push_str(r"\text{if } ");
+                condition.write_latex(out, TERNARY_PRECEDENCE + 1);
+                out.push_str(r" \text{ then } ");
+                then.write_latex(out, TERNARY_PRECEDENCE + 1);
+                out.push_str(r" \text{ else } ");
+                otherwise.write_latex(out, TERNARY_PRECEDENCE);
+            }
+            Expr::Quantified {
+                quantifier,
+                ty,
+                var,
+                body,
+            } => {
+                let quantifier = match quantifier {
+                    Quantifier::Forall => r"\forall",
+                    Quantifier::Exists => r"\exists",
+                };
+                write!(
+                    out,
+                    r"{quantifier} {} : \mathtt{{{}}}.\; ",
+                    latex_ident(var),
+                    escape_latex(ty)
+                )
+                .unwrap();
+                body.write_latex(out, TERNARY_PRECEDENCE);
+            }
+        }
+
+        if parenthesize {
+            out.push_str(r"\right)");
+        }
+    }
+
+    fn write_mathml(&self, out: &mut String, min_precedence: u8) {
+        let parenthesize = self.precedence() < min_precedence;
+        if parenthesize {
+            out.push_str("<mrow><mo>(</mo>");
+        }
+
+        match self {
+            Expr::Ident { name, .. } => write!(out, "<mi>{}</mi>", escape_xml(name)).unwrap(),
+            Expr::Number(number) => write!(out, "<mn>{}</mn>", escape_xml(number)).unwrap(),
+            Expr::Bool(value) => write!(out, "<mi mathvariant=\"normal\">{value}</mi>").unwrap(),
+            Expr::Call { name, args, .. } => {
+                write!(
+                    out,
+                    "<mi mathvariant=\"normal\">{}</mi><mo>(</mo>",
+                    escape_xml(name)
+                )
+                .unwrap();
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str("<mo>,</mo>");
+                    }
+                    arg.write_mathml(out, TERNARY_PRECEDENCE);
+                }
+                out.push_str("<mo>)</mo>");
+            }
+            Expr::Index { base, index } => {
+                base.write_mathml(out, ATOM_PRECEDENCE);
+                out.push_str("<mo>[</mo>");
+                index.write_mathml(out, TERNARY_PRECEDENCE);
+                out.push_str("<mo>]</mo>");
+            }
+            Expr::Unary { op, operand } => {
+                let op = match op {
+                    UnaryOp::Not => "&#x00AC;",
+                    UnaryOp::Neg => "-",
+                };
+                write!(out, "<mo>{op}</mo>").unwrap();
+                operand.write_mathml(out, UNARY_PRECEDENCE);
+            }
+            Expr::Binary {
+                op: BinaryOp::Pow,
+                lhs,
+                rhs,
+            } => {
+                out.push_str("<msup><mrow>");
+                lhs.write_mathml(out, ATOM_PRECEDENCE);
+                out.push_str("</mrow><mrow>");
+                rhs.write_mathml(out, TERNARY_PRECEDENCE);
+                out.push_str("</mrow></msup>");
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let (left, right) = Expr::binary_child_precedences(*op);
+                lhs.write_mathml(out, left);
+                write!(out, "<mo>{}</mo>", op.mathml()).unwrap();
+                rhs.write_mathml(out, right);
+            }
+            Expr::Ternary {
+                condition,
+                then,
+                otherwise,
+            } => {
+                out.push_str("<mtext>if&#x00A0;</mtext>");
+                condition.write_mathml(out, TERNARY_PRECEDENCE + 1);
+                out.push_str("<mtext>&#x00A0;then&#x00A0;</mtext>");
+                then.write_mathml(out, TERNARY_PRECEDENCE + 1);
+                out.push_str("<mtext>&#x00A0;else&#x00A0;</mtext>");
+                otherwise.write_mathml(out, TERNARY_PRECEDENCE);
+            }
+            Expr::Quantified {
+                quantifier,
+                ty,
+                var,
+                body,
+            } => {
+                let quantifier = match quantifier {
+                    Quantifier::Forall => "&#x2200;",
+                    Quantifier::Exists => "&#x2203;",
+                };
+                write!(
+                    out,
+                    "<mo>{quantifier}</mo><mi>{}</mi><mo>:</mo><mi mathvariant=\"monospace\">{}</mi><mo>.</mo>",
+                    escape_xml(var),
+                    escape_xml(ty)
+                )
+                .unwrap();
+                body.write_mathml(out, TERNARY_PRECEDENCE);
+            }
+        }
+
+        if parenthesize {
+            out.push_str("<mo>)</mo></mrow>");
+        }
+    }
+
+    pub fn to_latex(&self) -> String {
+        let mut out = String::new();
+        self.write_latex(&mut out, TERNARY_PRECEDENCE);
+        out
+    }
+
+    pub fn to_mathml(&self) -> String {
+        let mut out = String::new();
+        self.write_mathml(&mut out, TERNARY_PRECEDENCE);
+        format!("<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow>{out}</mrow></math>")
+    }
+
+    /// calls `f` with each name in the expression that is not bound by a quantifier,
+    /// and the names bound at that point
+    fn free_names<'a>(&'a self, bound: &mut Vec<&'a str>, f: &mut impl FnMut(&'a str, &Span)) {
+        match self {
+            Expr::Ident { name, span } => {
+                let head = name.split('.').next().unwrap_or(name);
+                if !bound.contains(&head) {
+                    f(head, span);
+                }
+            }
+            Expr::Call { name, span, args } => {
+                f(name.split('.').next().unwrap_or(name), span);
+                for arg in args {
+                    arg.free_names(bound, f);
+                }
+            }
+            Expr::Number(_) | Expr::Bool(_) => {}
+            Expr::Index { base, index } => {
+                base.free_names(bound, f);
+                index.free_names(bound, f);
+            }
+            Expr::Unary { operand, .. } => operand.free_names(bound, f),
+            Expr::Binary { lhs, rhs, .. } => {
+                lhs.free_names(bound, f);
+                rhs.free_names(bound, f);
+            }
+            Expr::Ternary {
+                condition,
+                then,
+                otherwise,
+            } => {
+                condition.free_names(bound, f);
+                then.free_names(bound, f);
+                otherwise.free_names(bound, f);
+            }
+            Expr::Quantified { var, body, .. } => {
+                bound.push(var);
+                body.free_names(bound, f);
+                bound.pop();
+            }
+        }
+    }
+}
+
+impl Formula {
+    pub fn to_latex(&self) -> String {
+        match self {
+            Formula::Cvl(expr) => expr.to_latex(),
+            Formula::Latex(latex) => latex.clone(),
+        }
+    }
+
+    /// LaTeX formulas are not converted, but embedded as an annotation for the renderer
+    pub fn to_mathml(&self) -> String {
+        match self {
+            Formula::Cvl(expr) => expr.to_mathml(),
+            Formula::Latex(latex) => format!(
+                "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><semantics><mrow></mrow>\
+                 <annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+                escape_xml(latex)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormulaProblemKind {
+    /// the formula is neither LaTeX nor a CVL expression
+    Unparsable(String),
+    /// a name that is not a parameter of the element, nor declared in the surrounding file
+    UnknownIdentifier(String),
+}
+
+/// a problem with a `@formula` tag, located in the source of the documentation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaProblem {
+    pub kind: FormulaProblemKind,
+    pub span: Span,
+}
+
+impl FormulaProblem {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let message = match &self.kind {
+            FormulaProblemKind::Unparsable(message) => message.clone(),
+            FormulaProblemKind::UnknownIdentifier(name) => {
+                format!("{name} is not a parameter or a known declaration")
+            }
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// finds the span in the source of the `occurrence`th appearance of `name` as a whole word
+/// in the tag, skipping the tag itself. the description of a tag has its comment markers
+/// removed, so spans in the description do not map directly to the source.
+fn name_span_in_tag(tag: &DocumentationTag, src: &str, name: &str, occurrence: usize) -> Span {
+    let is_word_char = |ch: char| ch.is_alphanumeric() || matches!(ch, '_' | '$' | '@' | '.');
+
+    let word_starts = |text: &str| {
+        text.match_indices(name)
+            .filter(|(i, _)| {
+                let before = text[..*i].chars().next_back();
+                let after = text[i + name.len()..].chars().next();
+                !before.is_some_and(is_word_char)
+                    && !after.is_some_and(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '$'))
+            })
+            .map(|(i, _)| text[..i].chars().count())
+            .collect::<Vec<_>>()
+    };
+
+    tag.span
+        .byte_slice(src)
+        .and_then(|text| word_starts(text).get(occurrence).copied())
+        .map(|start| {
+            let start = tag.span.start + start;
+            start..start + name.chars().count()
+        })
+        .unwrap_or_else(|| tag.span.clone())
+}
+
+/// the problems with the `@formula` tags of `element`. `context` holds the declarations
+/// that formulas may refer to, usually the elements of the file `element` is in.
+pub fn formula_problems(element: &CvlElement, context: &[CvlElement]) -> Vec<FormulaProblem> {
+    let mut known: HashSet<&str> = BUILTINS.into_iter().collect();
+    known.extend(context.iter().filter_map(|element| element.ast.name()));
+    known.extend(methods_entry_names(context));
+    known.extend(context.iter().filter_map(|element| match &element.ast {
+        Ast::Using { spec_name, .. } => Some(spec_name.as_str()),
+        _ => None,
+    }));
+    if let Some(params) = element.ast.params() {
+        known.extend(params.iter().map(|param| param.name.as_str()));
+    }
+
+    let mut problems = Vec::new();
+
+    for tag in element
+        .doc
+        .iter()
+        .filter(|tag| tag.kind == TagKind::Formula)
+    {
+        match parse_formula(&tag.description) {
+            Ok(Formula::Cvl(expr)) => {
+                let mut occurrences: Vec<&str> = Vec::new();
+                expr.free_names(&mut Vec::new(), &mut |name, _span| {
+                    let occurrence = occurrences.iter().filter(|seen| **seen == name).count();
+                    occurrences.push(name);
+
+                    if !known.contains(name) {
+                        problems.push(FormulaProblem {
+                            kind: FormulaProblemKind::UnknownIdentifier(name.to_string()),
+                            span: name_span_in_tag(tag, &element.src, name, occurrence),
+                        });
+                    }
+                });
+            }
+            Ok(Formula::Latex(_)) => {}
+            Err(error) => problems.push(FormulaProblem {
+                kind: FormulaProblemKind::Unparsable(error.message),
+                span: tag.span.clone(),
+            }),
+        }
+    }
+
+    problems
+}
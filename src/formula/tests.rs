@@ -0,0 +1,159 @@
+use super::{formula_problems, parse_formula, BinaryOp, Expr, Formula, FormulaProblemKind};
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use assert_matches::assert_matches;
+use indoc::indoc;
+use ropey::Rope;
+
+fn latex(formula: &str) -> String {
+    parse_formula(formula).unwrap().to_latex()
+}
+
+#[test]
+fn cvl_formulas_are_parsed() {
+    let formula = parse_formula("forall address a. balanceOf(a) <= totalSupply()").unwrap();
+    let Formula::Cvl(Expr::Quantified { ty, var, body, .. }) = formula else {
+        panic!("expected a quantified expression");
+    };
+    assert_eq!((ty.as_str(), var.as_str()), ("address", "a"));
+    assert_matches!(
+        *body,
+        Expr::Binary {
+            op: BinaryOp::Le,
+            ..
+        }
+    );
+
+    assert_matches!(
+        parse_formula("a <=> b => c").unwrap(),
+        Formula::Cvl(Expr::Binary {
+            op: BinaryOp::Iff,
+            ..
+        })
+    );
+    assert_matches!(
+        parse_formula("x > 0 ? e.msg.value : 1").unwrap(),
+        Formula::Cvl(Expr::Ternary { .. })
+    );
+
+    let error = parse_formula("balance(a) <= ").unwrap_err();
+    assert!(error.message.starts_with("formula is not a CVL expression"));
+}
+
+#[test]
+fn powers_bind_tighter_than_prefix_operators_and_associate_to_the_right() {
+    let Formula::Cvl(Expr::Unary { operand, .. }) = parse_formula("-x ** 2").unwrap() else {
+        panic!("expected a negation");
+    };
+    assert_matches!(
+        *operand,
+        Expr::Binary {
+            op: BinaryOp::Pow,
+            ..
+        }
+    );
+    assert_eq!(latex("-x ** 2"), "-x^{2}");
+    assert_eq!(latex("(-x) ** 2"), r"\left(-x\right)^{2}");
+
+    let Formula::Cvl(Expr::Binary { lhs, rhs, .. }) = parse_formula("a ** b ** c").unwrap() else {
+        panic!("expected a power");
+    };
+    assert_matches!(*lhs, Expr::Ident { .. });
+    assert_matches!(
+        *rhs,
+        Expr::Binary {
+            op: BinaryOp::Pow,
+            ..
+        }
+    );
+    assert_eq!(latex("a ** b ** c"), "a^{b^{c}}");
+    assert_eq!(latex("(a ** b) ** c"), r"\left(a^{b}\right)^{c}");
+
+    assert_eq!(latex("2 ** -n"), "2^{-n}");
+    assert_eq!(latex("-a ** b * c"), r"-a^{b} \cdot c");
+}
+
+#[test]
+fn latex_formulas_are_kept_as_is() {
+    assert_eq!(
+        parse_formula(r"$\sum_a b(a) = t$").unwrap(),
+        Formula::Latex(r"\sum_a b(a) = t".to_string())
+    );
+    assert_eq!(
+        parse_formula(r"\forall a.\ b(a) \leq t").unwrap(),
+        Formula::Latex(r"\forall a.\ b(a) \leq t".to_string())
+    );
+}
+
+#[test]
+fn formulas_are_rendered() {
+    assert_eq!(
+        latex("forall address a. balanceOf(a) <= totalSupply()"),
+        r"\forall a : \mathtt{address}.\; \mathrm{balanceOf}(a) \leq \mathrm{totalSupply}()"
+    );
+    assert_eq!(
+        latex("(a + b) * c == a * c + b * c"),
+        r"\left(a + b\right) \cdot c = a \cdot c + b \cdot c"
+    );
+    assert_eq!(latex("a - (b - c)"), r"a - \left(b - c\right)");
+    assert_eq!(
+        latex("!paused && 2 ** n != max_total"),
+        r"\lnot \mathit{paused} \land 2^{n} \neq \mathit{max\_total}"
+    );
+
+    let mathml = parse_formula("x[i] < 10").unwrap().to_mathml();
+    assert_eq!(
+        mathml,
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow>\
+         <mi>x</mi><mo>[</mo><mi>i</mi><mo>]</mo><mo>&lt;</mo><mn>10</mn>\
+         </mrow></math>"
+    );
+}
+
+#[test]
+fn unknown_identifiers_in_formulas_are_reported() {
+    let src = indoc! {"
+        using Token as token;
+
+        methods {
+            function balanceOf(address) external returns (uint) envfree;
+        }
+
+        definition cap() returns uint = 1000;
+
+        /// @formula forall address a. balanceOf(a) + amount <= cap()
+        /// @formula token.totalSupply() <= cap() &&
+        ///     amount <= totl
+        rule bounded(uint amount) { }
+
+        /**
+         * @formula totl
+         * + amount + totl
+         */
+        rule starred(uint amount) { }
+
+        /// @formula balanceOf(a) <=
+        rule broken { }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let converter = RangeConverter::new(Rope::from_str(src));
+
+    let problems = formula_problems(&elements[3], &elements);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].kind,
+        FormulaProblemKind::UnknownIdentifier("totl".to_string())
+    );
+    assert_eq!(converter.to_range(problems[0].span.clone()).start.line, 10);
+    assert_eq!(problems[0].span.byte_slice(src), Some("totl"));
+
+    let problems = formula_problems(&elements[4], &elements);
+    let lines = problems
+        .iter()
+        .map(|problem| converter.to_range(problem.span.clone()).start.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [14, 15]);
+
+    let problems = formula_problems(&elements[5], &elements);
+    assert_matches!(problems[0].kind, FormulaProblemKind::Unparsable(_));
+}
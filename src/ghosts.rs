@@ -0,0 +1,199 @@
+//! a report of where each ghost is written and read.
+
+use crate::graph::{DependencyGraph, EdgeKind, Node};
+use crate::id::ElementId;
+use crate::semantic::name_span;
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, CvlElement};
+use itertools::Itertools;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use serde::Serialize;
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// opcodes that make a call. a non-persistent ghost that is written in a hook on one of
+/// these is reverted along with the call, and havoced when the callee is unresolved.
+pub const CALL_OPCODES: [&str; 4] = ["CALL", "CALLCODE", "DELEGATECALL", "STATICCALL"];
+
+/// the elements that write and read a ghost
+#[derive(Debug, Clone, Serialize)]
+pub struct GhostFlow<'a> {
+    pub id: ElementId,
+    pub persistent: bool,
+    /// hooks, and any other element that assigns to the ghost
+    pub written_by: Vec<ElementId>,
+    pub read_by: Vec<ElementId>,
+    #[serde(skip)]
+    pub element: &'a CvlElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum GhostWarningKind {
+    /// the ghost is read, but no hook writes it
+    NeverWritten,
+    /// the ghost is not persistent, but is written in a hook on a call opcode
+    WrittenInCallHook { hook: ElementId, opcode: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GhostWarning<'a> {
+    pub ghost: ElementId,
+    #[serde(flatten)]
+    pub kind: GhostWarningKind,
+    /// the element the warning is reported on: the ghost, or the hook that writes it
+    #[serde(skip)]
+    pub element: &'a CvlElement,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+impl GhostWarning<'_> {
+    pub fn message(&self) -> String {
+        let name = &self.ghost.name;
+        match &self.kind {
+            GhostWarningKind::NeverWritten => {
+                format!("ghost {name} is read, but is not written by any hook")
+            }
+            GhostWarningKind::WrittenInCallHook { opcode, .. } => format!(
+                "ghost {name} is not persistent, so writes to it in a {opcode} hook may be reverted or havoced"
+            ),
+        }
+    }
+
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message: self.message(),
+            ..Default::default()
+        }
+    }
+}
+
+/// the data flow of every ghost in a [DependencyGraph]
+#[derive(Debug, Clone, Serialize)]
+pub struct GhostReport<'a> {
+    pub ghosts: Vec<GhostFlow<'a>>,
+    pub warnings: Vec<GhostWarning<'a>>,
+}
+
+fn is_hook(ast: &Ast) -> bool {
+    matches!(
+        ast,
+        Ast::HookSload { .. }
+            | Ast::HookSstore { .. }
+            | Ast::HookCreate { .. }
+            | Ast::HookOpcode { .. }
+    )
+}
+
+impl<'a> GhostReport<'a> {
+    pub fn new(graph: &DependencyGraph<'a>) -> GhostReport<'a> {
+        let mut ghosts = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (index, node) in graph.nodes.iter().enumerate() {
+            let persistent = match node.element.ast {
+                Ast::GhostFunction { persistent, .. } | Ast::GhostMapping { persistent, .. } => {
+                    persistent
+                }
+                _ => continue,
+            };
+
+            let sources = |kind: EdgeKind| -> Vec<&Node<'a>> {
+                graph
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.to == index && edge.kind == kind)
+                    .map(|edge| &graph.nodes[edge.from])
+                    .unique_by(|node| &node.id)
+                    .collect()
+            };
+            let writers = sources(EdgeKind::Writes);
+            let readers = sources(EdgeKind::Reads)
+                .into_iter()
+                .chain(sources(EdgeKind::Calls))
+                .collect::<Vec<_>>();
+
+            if !readers.is_empty() && !writers.iter().any(|writer| is_hook(&writer.element.ast)) {
+                warnings.push(GhostWarning {
+                    ghost: node.id.clone(),
+                    kind: GhostWarningKind::NeverWritten,
+                    element: node.element,
+                    span: name_span(node.element, &node.id.name),
+                });
+            }
+
+            if !persistent {
+                for writer in &writers {
+                    let Ast::HookOpcode { opcode, .. } = &writer.element.ast else {
+                        continue;
+                    };
+                    if CALL_OPCODES.contains(&opcode.as_str()) {
+                        warnings.push(GhostWarning {
+                            ghost: node.id.clone(),
+                            kind: GhostWarningKind::WrittenInCallHook {
+                                hook: writer.id.clone(),
+                                opcode: opcode.clone(),
+                            },
+                            element: writer.element,
+                            span: name_span(writer.element, &node.id.name),
+                        });
+                    }
+                }
+            }
+
+            ghosts.push(GhostFlow {
+                id: node.id.clone(),
+                persistent,
+                written_by: writers.iter().map(|node| node.id.clone()).collect(),
+                read_by: readers.iter().map(|node| node.id.clone()).collect(),
+                element: node.element,
+            });
+        }
+
+        GhostReport { ghosts, warnings }
+    }
+
+    /// a table of the ghosts, followed by the warnings
+    pub fn to_markdown(&self) -> String {
+        let list = |ids: &[ElementId]| {
+            if ids.is_empty() {
+                "-".to_string()
+            } else {
+                ids.iter().map(|id| format!("`{}`", id.name)).join(", ")
+            }
+        };
+
+        let mut markdown = String::from("| ghost | persistent | written by | read by |\n");
+        markdown.push_str("| --- | --- | --- | --- |\n");
+
+        for ghost in &self.ghosts {
+            writeln!(
+                markdown,
+                "| `{}` | {} | {} | {} |",
+                ghost.id.name,
+                if ghost.persistent { "yes" } else { "no" },
+                list(&ghost.written_by),
+                list(&ghost.read_by)
+            )
+            .unwrap();
+        }
+
+        if !self.warnings.is_empty() {
+            markdown.push_str("\n### Warnings\n");
+            for warning in &self.warnings {
+                writeln!(markdown, "- {}", warning.message()).unwrap();
+            }
+        }
+
+        markdown
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the report is serializable")
+    }
+}
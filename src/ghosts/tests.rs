@@ -0,0 +1,114 @@
+use super::{GhostReport, GhostWarningKind};
+use crate::graph::DependencyGraph;
+use crate::id::ElementId;
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use assert_matches::assert_matches;
+use indoc::{formatdoc, indoc};
+use ropey::Rope;
+
+const SRC: &str = indoc! {"
+    ghost mapping(address => uint) balances;
+    persistent ghost uint calls;
+    ghost uint lastValue;
+    ghost bool neverWritten;
+
+    hook Sstore _balances[KEY address a] uint v (uint oldValue) STORAGE {
+        balances[a] = v;
+    }
+
+    hook CALL(uint g, address addr, uint value, uint argsOffset, uint argsLength, uint retOffset, uint retLength) uint rc {
+        calls = calls + 1;
+        lastValue = value;
+    }
+
+    invariant solvent(address a) balances[a] >= 0 && calls >= 0;
+
+    rule r {
+        assert !neverWritten;
+    }
+"};
+
+#[test]
+fn writers_and_readers_of_each_ghost() {
+    let elements = Builder::new(SRC).build().unwrap();
+    let graph = DependencyGraph::new(&[("token.spec", &elements)]);
+    let report = GhostReport::new(&graph);
+
+    let flows = report
+        .ghosts
+        .iter()
+        .map(|ghost| {
+            let names =
+                |ids: &[ElementId]| ids.iter().map(|id| id.name.clone()).collect::<Vec<_>>();
+            (
+                ghost.id.name.as_str(),
+                ghost.persistent,
+                names(&ghost.written_by),
+                names(&ghost.read_by),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flows,
+        [
+            (
+                "balances",
+                false,
+                vec!["Sstore _balances[KEY address a]".to_string()],
+                vec!["solvent".to_string()]
+            ),
+            (
+                "calls",
+                true,
+                vec!["CALL".to_string()],
+                vec!["CALL".to_string(), "solvent".to_string()]
+            ),
+            ("lastValue", false, vec!["CALL".to_string()], vec![]),
+            ("neverWritten", false, vec![], vec!["r".to_string()]),
+        ]
+    );
+
+    assert_eq!(report.warnings.len(), 2);
+    assert_matches!(
+        &report.warnings[0].kind,
+        GhostWarningKind::WrittenInCallHook { opcode, .. } if opcode == "CALL"
+    );
+    assert_eq!(report.warnings[0].span.byte_slice(SRC), Some("lastValue"));
+    assert_eq!(report.warnings[1].kind, GhostWarningKind::NeverWritten);
+
+    let converter = RangeConverter::new(Rope::from_str(SRC));
+    let diagnostic = report.warnings[1].to_diagnostic(&converter);
+    assert_eq!(diagnostic.range.start.line, 3);
+    assert_eq!(
+        diagnostic.message,
+        "ghost neverWritten is read, but is not written by any hook"
+    );
+}
+
+#[test]
+fn markdown_and_json() {
+    let elements = Builder::new(SRC).build().unwrap();
+    let graph = DependencyGraph::new(&[("token.spec", &elements)]);
+    let report = GhostReport::new(&graph);
+
+    let expected = formatdoc! {"
+        | ghost | persistent | written by | read by |
+        | --- | --- | --- | --- |
+        | `balances` | no | `Sstore _balances[KEY address a]` | `solvent` |
+        | `calls` | yes | `CALL` | `CALL`, `solvent` |
+        | `lastValue` | no | `CALL` | - |
+        | `neverWritten` | no | - | `r` |
+
+        ### Warnings
+        - ghost lastValue is not persistent, so writes to it in a CALL hook may be reverted or havoced
+        - ghost neverWritten is read, but is not written by any hook
+    "};
+    assert_eq!(report.to_markdown(), expected);
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["ghosts"][1]["persistent"], true);
+    assert_eq!(json["warnings"][0]["kind"], "writtenInCallHook");
+    assert_eq!(json["warnings"][0]["opcode"], "CALL");
+    assert_eq!(json["warnings"][1]["ghost"]["name"], "neverWritten");
+}
@@ -0,0 +1,285 @@
+//! a graph of the dependencies between elements: calls, `requireInvariant`s,
+//! `use` statements, and the ghosts that are read and written.
+
+use crate::id::{element_ids, ElementId};
+use crate::project::SpecProject;
+use crate::references::is_assigned_in;
+use crate::symbols::{Resolution, SymbolKind, SymbolTable};
+use crate::util::ByteSpan;
+use crate::{Ast, CvlElement};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Write};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EdgeKind {
+    /// a call to a definition, a CVL function or a ghost function
+    Calls,
+    /// a `requireInvariant`, including those in `preserved` blocks
+    RequiresInvariant,
+    /// a `use rule` or `use invariant` statement
+    Uses,
+    /// a ghost that is read
+    Reads,
+    /// a ghost that is assigned to, as in the body of a hook
+    Writes,
+}
+
+impl Display for EdgeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            EdgeKind::Calls => "calls",
+            EdgeKind::RequiresInvariant => "requireInvariant",
+            EdgeKind::Uses => "uses",
+            EdgeKind::Reads => "reads",
+            EdgeKind::Writes => "writes",
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Node<'a> {
+    pub id: ElementId,
+    /// the kind of the element, and its name
+    pub label: String,
+    #[serde(skip)]
+    pub element: &'a CvlElement,
+}
+
+/// an edge between two nodes, given by their indices in [DependencyGraph::nodes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// the elements of a set of files, and the dependencies between them.
+/// every element is a node, except for freeform comments, imports and `using` statements.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub edges: Vec<Edge>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    /// the graph of the elements of each file, given with the path of the file
+    pub fn new(files: &[(&str, &'a [CvlElement])]) -> DependencyGraph<'a> {
+        let table = SymbolTable::new(files);
+
+        let nodes = files
+            .iter()
+            .flat_map(|&(path, elements)| elements.iter().zip(element_ids(path, elements)))
+            .filter(|(element, _)| {
+                !matches!(
+                    element.ast,
+                    Ast::FreeFormComment { .. } | Ast::Import { .. } | Ast::Using { .. }
+                )
+            })
+            .map(|(element, id)| Node {
+                label: format!("{} {}", element.ast, id.name),
+                id,
+                element,
+            })
+            .collect::<Vec<_>>();
+
+        let index: HashMap<*const CvlElement, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.element as *const _, i))
+            .collect();
+
+        let mut edges = BTreeSet::new();
+
+        for (from, node) in nodes.iter().enumerate() {
+            let element = node.element;
+
+            let used = match &element.ast {
+                Ast::UseRule { name, .. } => Some((name, SymbolKind::Rule)),
+                Ast::UseInvariant { name, .. } => Some((name, SymbolKind::Invariant)),
+                _ => None,
+            };
+            if let Some((name, kind)) = used {
+                for symbol in table.lookup(name) {
+                    if symbol.kind == kind {
+                        let to = index[&(symbol.element as *const _)];
+                        edges.insert(Edge {
+                            from,
+                            to,
+                            kind: EdgeKind::Uses,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let code = element
+                .element_span
+                .byte_slice(&element.src)
+                .unwrap_or_default();
+            let mut after_require_invariant = false;
+
+            for reference in element.references() {
+                let is_required = std::mem::replace(
+                    &mut after_require_invariant,
+                    reference.name == "requireInvariant",
+                );
+                let Some(Resolution::Declarations(symbols)) =
+                    table.resolve_reference(element, &reference)
+                else {
+                    continue;
+                };
+                let is_assigned =
+                    is_assigned_in(code, reference.span.end - element.element_span.start);
+
+                for symbol in symbols {
+                    if std::ptr::eq(symbol.element, element) {
+                        continue;
+                    }
+
+                    let kind = match symbol.kind {
+                        SymbolKind::Invariant if is_required => EdgeKind::RequiresInvariant,
+                        SymbolKind::GhostFunction | SymbolKind::GhostMapping if is_assigned => {
+                            EdgeKind::Writes
+                        }
+                        SymbolKind::GhostMapping => EdgeKind::Reads,
+                        SymbolKind::GhostFunction if reference.is_call => EdgeKind::Calls,
+                        SymbolKind::GhostFunction => EdgeKind::Reads,
+                        SymbolKind::Definition | SymbolKind::Function => EdgeKind::Calls,
+                        _ => continue,
+                    };
+
+                    let to = index[&(symbol.element as *const _)];
+                    edges.insert(Edge { from, to, kind });
+                }
+            }
+        }
+
+        DependencyGraph {
+            nodes,
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// the graph of every file in the project
+    pub fn for_project(project: &'a SpecProject) -> DependencyGraph<'a> {
+        let files = project
+            .files
+            .iter()
+            .map(|file| {
+                let path = file.path.to_str().unwrap_or_default();
+                (path, file.spec.elements.as_slice())
+            })
+            .collect::<Vec<_>>();
+
+        DependencyGraph::new(&files)
+    }
+
+    /// the indices of the nodes whose element has the given name
+    pub fn find(&self, name: &str) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .positions(|node| node.element.ast.name() == Some(name))
+            .collect()
+    }
+
+    /// the subgraph of the nodes that are reachable from `roots` by following edges.
+    /// since the value of a ghost depends on what writes it, the writers of every
+    /// reachable ghost are included too, as are the nodes reachable from them.
+    pub fn reachable_from(&self, roots: &[usize]) -> DependencyGraph<'a> {
+        let mut reachable = BTreeSet::new();
+        let mut stack = roots.to_vec();
+
+        while let Some(node) = stack.pop() {
+            if !reachable.insert(node) {
+                continue;
+            }
+
+            for edge in &self.edges {
+                if edge.from == node {
+                    stack.push(edge.to);
+                }
+                if edge.to == node && edge.kind == EdgeKind::Writes {
+                    stack.push(edge.from);
+                }
+            }
+        }
+
+        let new_index: HashMap<usize, usize> = reachable
+            .iter()
+            .enumerate()
+            .map(|(new, &old)| (old, new))
+            .collect();
+
+        DependencyGraph {
+            nodes: reachable
+                .iter()
+                .map(|&node| self.nodes[node].clone())
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter_map(|edge| {
+                    Some(Edge {
+                        from: *new_index.get(&edge.from)?,
+                        to: *new_index.get(&edge.to)?,
+                        kind: edge.kind,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// the graph in the DOT language of Graphviz
+    pub fn to_dot(&self) -> String {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut dot = String::from("digraph spec {\n");
+
+        for node in &self.nodes {
+            let shape = match node.element.ast {
+                Ast::Rule { .. } | Ast::UseRule { .. } => "box",
+                Ast::Invariant { .. } | Ast::UseInvariant { .. } => "doubleoctagon",
+                Ast::GhostFunction { .. } | Ast::GhostMapping { .. } => "cylinder",
+                Ast::HookSload { .. }
+                | Ast::HookSstore { .. }
+                | Ast::HookCreate { .. }
+                | Ast::HookOpcode { .. } => "cds",
+                _ => "ellipse",
+            };
+            writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", shape={shape}];",
+                escape(&node.id.to_string()),
+                escape(&node.label)
+            )
+            .unwrap();
+        }
+
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape(&self.nodes[edge.from].id.to_string()),
+                escape(&self.nodes[edge.to].id.to_string()),
+                edge.kind
+            )
+            .unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// the graph as JSON: a list of nodes, and a list of edges between node indices
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("the graph is serializable")
+    }
+}
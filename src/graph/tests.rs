@@ -0,0 +1,132 @@
+use super::{DependencyGraph, EdgeKind};
+use crate::parse::builder::Builder;
+use indoc::indoc;
+
+const SRC: &str = indoc! {"
+    ghost mapping(address => uint) balances;
+    ghost uint total;
+
+    hook Sstore _balances[KEY address a] uint v (uint oldValue) STORAGE {
+        balances[a] = v;
+    }
+
+    definition MAX() returns uint = 1000;
+    function clamp(uint x) returns uint { return x; }
+
+    invariant positive(address a) balances[a] >= 0;
+
+    invariant bounded(address a) balances[a] <= MAX()
+    {
+        preserved {
+            requireInvariant positive(a);
+        }
+    }
+
+    rule r(address a) {
+        assert clamp(balances[a]) <= MAX();
+    }
+
+    rule other {
+        assert total == 0;
+    }
+
+    use rule r;
+"};
+
+#[test]
+fn typed_edges_between_elements() {
+    let elements = Builder::new(SRC).build().unwrap();
+    let graph = DependencyGraph::new(&[("token.spec", &elements)]);
+
+    let labels = graph
+        .nodes
+        .iter()
+        .map(|node| node.label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "ghost balances",
+            "ghost total",
+            "hook Sstore _balances[KEY address a]",
+            "definition MAX",
+            "function clamp(uint)",
+            "invariant positive",
+            "invariant bounded",
+            "rule r",
+            "rule other",
+            "use rule r",
+        ]
+    );
+
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            (
+                graph.nodes[edge.from].id.name.as_str(),
+                edge.kind,
+                graph.nodes[edge.to].id.name.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        [
+            (
+                "Sstore _balances[KEY address a]",
+                EdgeKind::Writes,
+                "balances"
+            ),
+            ("positive", EdgeKind::Reads, "balances"),
+            ("bounded", EdgeKind::Reads, "balances"),
+            ("bounded", EdgeKind::Calls, "MAX"),
+            ("bounded", EdgeKind::RequiresInvariant, "positive"),
+            ("r", EdgeKind::Reads, "balances"),
+            ("r", EdgeKind::Calls, "MAX"),
+            ("r", EdgeKind::Calls, "clamp(uint)"),
+            ("other", EdgeKind::Reads, "total"),
+            ("rule r", EdgeKind::Uses, "r"),
+        ]
+    );
+}
+
+#[test]
+fn reachability_and_export() {
+    let elements = Builder::new(SRC).build().unwrap();
+    let graph = DependencyGraph::new(&[("token.spec", &elements)]);
+
+    let roots = graph.find("r");
+    assert_eq!(roots.len(), 1);
+
+    // the hook is included, because it writes a ghost that the rule reads
+    let reachable = graph.reachable_from(&roots);
+    let names = reachable
+        .nodes
+        .iter()
+        .map(|node| node.id.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "balances",
+            "Sstore _balances[KEY address a]",
+            "MAX",
+            "clamp(uint)",
+            "r"
+        ]
+    );
+    assert_eq!(reachable.edges.len(), 4);
+
+    let dot = reachable.to_dot();
+    assert!(dot.starts_with("digraph spec {\n"));
+    assert!(dot.contains("    \"token.spec#rule-r\" [label=\"rule r\", shape=box];\n"));
+    assert!(dot.contains(
+        "    \"token.spec#rule-r\" -> \"token.spec#definition-MAX\" [label=\"calls\"];\n"
+    ));
+
+    let json: serde_json::Value = serde_json::from_str(&reachable.to_json()).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 5);
+    assert_eq!(json["nodes"][4]["label"], "rule r");
+    assert_eq!(json["edges"][0]["kind"], "writes");
+}
@@ -0,0 +1,111 @@
+//! identifiers for elements that do not depend on their position in the file.
+
+use crate::signature::normalize_type;
+use crate::{Ast, CvlElement};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[cfg(test)]
+mod tests;
+
+/// identifies an element by its file, its kind and its name.
+/// elements without a name are given a synthetic name, derived from their contents.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct ElementId {
+    pub path: String,
+    /// the kind of the element, as displayed by [Ast]
+    pub kind: String,
+    pub name: String,
+    /// counts the earlier elements in the same file with the same kind and name, if any
+    pub occurrence: usize,
+}
+
+impl ElementId {
+    /// an anchor for the element, unique within its file.
+    /// it only changes when the kind or name of the element changes.
+    pub fn slug(&self) -> String {
+        let mut slug = slugify(&format!("{} {}", self.kind, self.name));
+        if self.occurrence > 0 {
+            slug = format!("{slug}-{}", self.occurrence + 1);
+        }
+
+        slug
+    }
+}
+
+impl Display for ElementId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.path, self.slug())
+    }
+}
+
+/// replaces every run of characters that are not allowed in an anchor with a single dash
+pub fn slugify(text: &str) -> String {
+    text.split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+        .filter(|part| !part.is_empty())
+        .join("-")
+}
+
+impl Ast {
+    /// the name of the element, or a name that is derived from its contents.
+    /// CVL functions, which may be overloaded, include the types of their parameters.
+    pub fn id_name(&self) -> String {
+        match self {
+            Ast::Function { name, params, .. } => {
+                let types = params
+                    .iter()
+                    .map(|param| normalize_type(&param.ty))
+                    .join(",");
+                format!("{name}({types})")
+            }
+            Ast::Rule { name, .. }
+            | Ast::Invariant { name, .. }
+            | Ast::Definition { name, .. }
+            | Ast::GhostFunction { name, .. }
+            | Ast::GhostMapping { name, .. } => name.clone(),
+            Ast::UseRule { name, .. } => format!("rule {name}"),
+            Ast::UseInvariant { name, .. } => format!("invariant {name}"),
+            Ast::UseBuiltinRule { name } => format!("builtin rule {name}"),
+            Ast::Import { imported } => imported.clone(),
+            Ast::Using { spec_name, .. } => spec_name.clone(),
+            Ast::Methods { .. } => "methods".to_string(),
+            Ast::HookSload { slot_pattern, .. } => {
+                format!("Sload {}", normalize_type(slot_pattern))
+            }
+            Ast::HookSstore { slot_pattern, .. } => {
+                format!("Sstore {}", normalize_type(slot_pattern))
+            }
+            Ast::HookCreate { .. } => "Create".to_string(),
+            Ast::HookOpcode { opcode, .. } => opcode.clone(),
+            Ast::FreeFormComment { .. } => {
+                self.signature().trim_start_matches('#').trim().to_string()
+            }
+        }
+    }
+}
+
+/// the identifiers of `elements`, in the same order, for the file at `path`
+pub fn element_ids(path: &str, elements: &[CvlElement]) -> Vec<ElementId> {
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+
+    elements
+        .iter()
+        .map(|element| {
+            let kind = element.ast.to_string();
+            let name = element.ast.id_name();
+
+            let count = seen.entry((kind.clone(), name.clone())).or_default();
+            let occurrence = *count;
+            *count += 1;
+
+            ElementId {
+                path: path.to_string(),
+                kind,
+                name,
+                occurrence,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,65 @@
+use super::{element_ids, slugify};
+use crate::parse::builder::Builder;
+use indoc::indoc;
+
+#[test]
+fn element_ids_are_stable_and_unique() {
+    let src = indoc! {"
+        import \"erc20.spec\";
+        using Token as token;
+
+        function clamp(uint x) returns uint { return x; }
+        function clamp(uint x, uint  max) returns uint { return x; }
+
+        hook Sstore balances[KEY address a] uint v (uint oldValue) STORAGE { }
+        hook Sstore balances[KEY address a] uint v (uint oldValue) STORAGE { }
+
+        use rule transferIntegrity;
+        use invariant solvency;
+
+        rule transferIntegrity { }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let ids = element_ids("specs/token.spec", &elements);
+
+    let slugs = ids.iter().map(|id| id.slug()).collect::<Vec<_>>();
+    assert_eq!(
+        slugs,
+        [
+            "import-erc20-spec",
+            "using-token",
+            "function-clamp-uint",
+            "function-clamp-uint-uint",
+            "hook-Sstore-balances-KEY-address-a",
+            "hook-Sstore-balances-KEY-address-a-2",
+            "use-rule-transferIntegrity",
+            "use-invariant-solvency",
+            "rule-transferIntegrity",
+        ]
+    );
+    assert_eq!(ids[3].name, "clamp(uint,uint)");
+    assert_eq!(
+        ids[8].to_string(),
+        "specs/token.spec#rule-transferIntegrity"
+    );
+
+    // reordering the elements, or editing unrelated ones, does not change the ids
+    let reordered = indoc! {"
+        rule transferIntegrity { assert true; }
+
+        use invariant solvency;
+    "};
+    let elements = Builder::new(reordered).build().unwrap();
+    let reordered_ids = element_ids("specs/token.spec", &elements);
+    assert_eq!(reordered_ids[0], ids[8]);
+    assert_eq!(reordered_ids[1], ids[7]);
+}
+
+#[test]
+fn slugs() {
+    assert_eq!(
+        slugify("rule  transfer--Integrity!"),
+        "rule-transfer-Integrity"
+    );
+    assert_eq!(slugify("(x)"), "x");
+}
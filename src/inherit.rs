@@ -0,0 +1,158 @@
+//! documentation inheritance across files.
+//!
+//! `use rule` and `use invariant` statements automatically inherit the documentation
+//! of the rule or invariant they use. rules, invariants, definitions and CVL functions
+//! may inherit the documentation of an imported element of the same kind with `@inheritdoc`,
+//! or `@inheritdoc otherName` to inherit from an element with a different name.
+
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, CvlElement, DocumentationTag, TagKind};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+#[cfg(test)]
+mod tests;
+
+/// an `@inheritdoc` tag whose target could not be found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedInheritance {
+    pub target: String,
+    pub span: Span,
+}
+
+impl UnresolvedInheritance {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: format!("cannot inherit documentation: {} not found", self.target),
+            ..Default::default()
+        }
+    }
+}
+
+/// the name of the element itself, or of the element being used
+fn own_name(ast: &Ast) -> Option<&str> {
+    match ast {
+        Ast::UseRule { name, .. } | Ast::UseInvariant { name, .. } => Some(name),
+        _ => ast.name(),
+    }
+}
+
+/// whether `source` is an element that `ast` can inherit documentation from
+fn can_inherit_from(ast: &Ast, source: &Ast) -> bool {
+    match ast {
+        Ast::UseRule { .. } => matches!(source, Ast::Rule { .. }),
+        Ast::UseInvariant { .. } => matches!(source, Ast::Invariant { .. }),
+        _ => std::mem::discriminant(ast) == std::mem::discriminant(source),
+    }
+}
+
+/// the `@inheritdoc` tag of `element`, if it has one, and the name of the element it inherits from.
+/// `None` if it does not inherit documentation.
+fn inheritance_target(element: &CvlElement) -> Option<(Option<&DocumentationTag>, String)> {
+    let inherit_tag = element
+        .doc
+        .iter()
+        .find(|tag| tag.kind == TagKind::InheritDoc);
+    let automatic = matches!(element.ast, Ast::UseRule { .. } | Ast::UseInvariant { .. });
+
+    if inherit_tag.is_none() && !automatic {
+        return None;
+    }
+
+    let explicit_target = inherit_tag.and_then(|tag| tag.description.split_whitespace().next());
+    let target = explicit_target.or_else(|| own_name(&element.ast))?;
+
+    Some((inherit_tag, target.to_string()))
+}
+
+fn find_source<'a>(
+    ast: &Ast,
+    target: &str,
+    imported: &[&'a [CvlElement]],
+) -> Option<&'a CvlElement> {
+    imported
+        .iter()
+        .copied()
+        .flatten()
+        .find(|source| source.ast.name() == Some(target) && can_inherit_from(ast, &source.ast))
+}
+
+/// the imported element that `element` inherits its documentation from, if any
+pub(crate) fn inherited_from<'a>(
+    element: &CvlElement,
+    imported: &[&'a [CvlElement]],
+) -> Option<&'a CvlElement> {
+    let (_, target) = inheritance_target(element)?;
+    find_source(&element.ast, &target, imported)
+}
+
+/// copies the documentation of elements from `imported` files into `elements`.
+/// tags that are written on the inheriting element override the inherited tags of the same kind,
+/// or in the case of `@param`, the inherited tag of the same parameter.
+///
+/// since inherited tags come from another file, their span is set to the span
+/// of the `@inheritdoc` tag, or if there is none, to the span of the inheriting element.
+pub fn inherit_docs(
+    elements: &mut [CvlElement],
+    imported: &[&[CvlElement]],
+) -> Vec<UnresolvedInheritance> {
+    let mut unresolved = Vec::new();
+
+    for element in elements {
+        let Some((inherit_tag, target)) = inheritance_target(element) else {
+            continue;
+        };
+
+        let inherited_span = match inherit_tag {
+            Some(tag) => tag.span.clone(),
+            None => element.element_span.clone(),
+        };
+
+        let Some(source) = find_source(&element.ast, &target, imported) else {
+            // dangling `use` statements are reported elsewhere
+            if inherit_tag.is_some() {
+                unresolved.push(UnresolvedInheritance {
+                    target,
+                    span: inherited_span,
+                });
+            }
+            continue;
+        };
+
+        let local = std::mem::take(&mut element.doc);
+        element.doc = merge(&source.doc, local, inherited_span);
+    }
+
+    unresolved
+}
+
+fn merge(
+    inherited: &[DocumentationTag],
+    local: Vec<DocumentationTag>,
+    inherited_span: Span,
+) -> Vec<DocumentationTag> {
+    let overridden = |tag: &DocumentationTag| {
+        local.iter().any(|local_tag| match tag.kind {
+            TagKind::Param => {
+                local_tag.param_name().is_some() && local_tag.param_name() == tag.param_name()
+            }
+            _ => local_tag.kind == tag.kind,
+        })
+    };
+
+    let inherited = inherited
+        .iter()
+        .filter(|tag| tag.kind != TagKind::InheritDoc && !overridden(tag))
+        .map(|tag| DocumentationTag {
+            span: inherited_span.clone(),
+            ..tag.clone()
+        })
+        .collect::<Vec<_>>();
+
+    let local = local
+        .into_iter()
+        .filter(|tag| tag.kind != TagKind::InheritDoc);
+
+    inherited.into_iter().chain(local).collect()
+}
@@ -0,0 +1,167 @@
+use super::inherit_docs;
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use crate::{DocumentationTag, TagKind};
+use indoc::indoc;
+use itertools::Itertools;
+use ropey::Rope;
+
+const IMPORTED: &str = indoc! {"
+    /**
+     * @title Transfer integrity
+     * @notice transfers move exactly the transferred amount
+     * @param f the method being checked
+     * @dev slow
+     */
+    rule transferIntegrity(method f) { }
+
+    /// @notice total supply is the sum of balances
+    invariant totalSupplyIsSumOfBalances() true;
+
+    /// @notice computes the fee
+    /// @return the fee
+    function fee(uint256 amount) returns uint256 { return 0; }
+
+    /// @notice the largest fee that may be charged
+    definition MAX_FEE() returns uint256 = 100;
+"};
+
+#[test]
+fn use_statements_inherit_documentation() {
+    let src = indoc! {"
+        /// @dev fast, with the harness
+        use rule transferIntegrity;
+
+        use invariant totalSupplyIsSumOfBalances;
+
+        use rule notImported;
+    "};
+
+    let imported = Builder::new(IMPORTED).build().unwrap();
+    let mut elements = Builder::new(src).build().unwrap();
+    let unresolved = inherit_docs(&mut elements, &[&imported]);
+    assert!(unresolved.is_empty());
+
+    let kinds_and_descriptions = |i: usize| {
+        elements[i]
+            .doc
+            .iter()
+            .map(|tag| (tag.kind.clone(), tag.description.as_str()))
+            .collect_vec()
+    };
+
+    assert_eq!(
+        kinds_and_descriptions(0),
+        [
+            (TagKind::Title, "Transfer integrity"),
+            (
+                TagKind::Notice,
+                "transfers move exactly the transferred amount"
+            ),
+            (TagKind::Param, "f the method being checked"),
+            (TagKind::Dev, "fast, with the harness"),
+        ]
+    );
+    assert_eq!(
+        kinds_and_descriptions(1),
+        [(TagKind::Notice, "total supply is the sum of balances")]
+    );
+    assert!(elements[2].doc.is_empty());
+
+    // inherited tags point at the inheriting element
+    let inherited_title = &elements[0].doc[0];
+    assert_eq!(
+        inherited_title.span.byte_slice(src),
+        Some("use rule transferIntegrity;")
+    );
+    let converter = RangeConverter::new(Rope::from_str(src));
+    assert!(elements[0].enumerate_diagnostics(converter).is_empty());
+}
+
+#[test]
+fn explicit_inheritdoc() {
+    let src = indoc! {"
+        /// @inheritdoc
+        /// @return the fee, rounded up
+        function fee(uint256 amount) returns uint256 { return 1; }
+
+        /// @inheritdoc fee
+        function otherFee(uint256 amount) returns uint256 { return 2; }
+
+        /// @inheritdoc missing
+        function nothing() { }
+    "};
+
+    let imported = Builder::new(IMPORTED).build().unwrap();
+    let mut elements = Builder::new(src).build().unwrap();
+    let unresolved = inherit_docs(&mut elements, &[&imported]);
+
+    let [fee, other_fee, nothing] = elements.as_slice() else {
+        panic!("expected exactly 3 elements")
+    };
+
+    let descriptions =
+        |doc: &[DocumentationTag]| doc.iter().map(|tag| tag.description.clone()).collect_vec();
+    assert_eq!(
+        descriptions(&fee.doc),
+        ["computes the fee", "the fee, rounded up"]
+    );
+    assert_eq!(
+        descriptions(&other_fee.doc),
+        ["computes the fee", "the fee"]
+    );
+    assert_eq!(nothing.doc[0].kind, TagKind::InheritDoc);
+
+    let unresolved = unresolved.into_iter().exactly_one().unwrap();
+    assert_eq!(unresolved.target, "missing");
+    assert_eq!(
+        unresolved.span.byte_slice(src),
+        Some("/// @inheritdoc missing\n")
+    );
+}
+
+#[test]
+fn rules_invariants_and_definitions_inherit_documentation() {
+    let src = indoc! {"
+        /// @inheritdoc transferIntegrity
+        /// @dev with the harness
+        rule transferIntegrityHarness(method f) { }
+
+        /// @inheritdoc totalSupplyIsSumOfBalances
+        invariant supplyInvariant() true;
+
+        /// @inheritdoc
+        definition MAX_FEE() returns uint256 = 50;
+    "};
+
+    let imported = Builder::new(IMPORTED).build().unwrap();
+    let mut elements = Builder::new(src).build().unwrap();
+
+    for element in &elements {
+        let converter = RangeConverter::new(Rope::from_str(src));
+        assert!(element.enumerate_diagnostics(converter).is_empty());
+    }
+
+    let unresolved = inherit_docs(&mut elements, &[&imported]);
+    assert!(unresolved.is_empty());
+
+    let kinds = |doc: &[DocumentationTag]| doc.iter().map(|tag| tag.kind.clone()).collect_vec();
+    assert_eq!(
+        kinds(&elements[0].doc),
+        [
+            TagKind::Title,
+            TagKind::Notice,
+            TagKind::Param,
+            TagKind::Dev
+        ]
+    );
+    assert_eq!(elements[0].doc[3].description, "with the harness");
+    assert_eq!(
+        elements[1].doc[0].description,
+        "total supply is the sum of balances"
+    );
+    assert_eq!(
+        elements[2].doc[0].description,
+        "the largest fee that may be charged"
+    );
+}
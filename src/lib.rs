@@ -1,5 +1,26 @@
+pub mod bundle;
+pub mod deprecation;
 pub mod diagnostics;
+pub mod examples;
+pub mod export;
+pub mod formula;
+pub mod ghosts;
+pub mod graph;
+pub mod id;
+pub mod inherit;
+pub mod links;
+pub mod migrate;
+pub mod outline;
 pub mod parse;
+pub mod project;
+pub mod references;
+pub mod requirements;
+pub mod semantic;
+#[cfg(feature = "lsp")]
+pub mod server;
+pub mod signature;
+pub mod symbols;
+pub mod unused;
 pub mod util;
 
 use color_eyre::eyre::bail;
@@ -14,10 +35,31 @@ pub struct CvlElement {
     pub ast: Ast,
     pub element_span: Span,
     pub doc_span: Option<Span>,
+    pub nested: Vec<NestedElement>,
     #[serde(skip)]
     pub src: Arc<str>,
 }
 
+/// an entry inside the block of a [CvlElement], such as a single declaration in a `methods` block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NestedElement {
+    pub kind: NestedKind,
+    pub doc: Vec<DocumentationTag>,
+    pub doc_span: Option<Span>,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NestedKind {
+    /// a single declaration in a `methods` block
+    MethodsEntry,
+    /// a `preserved` block in the proof of an invariant
+    Preserved,
+    /// a single axiom of a ghost
+    Axiom,
+}
+
 impl Debug for CvlElement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CvlElement")
@@ -141,20 +183,40 @@ impl CvlElement {
             }
         });
         let from_name = || self.ast.name().map(ToOwned::to_owned);
+        let from_signature = || match self.ast {
+            Ast::FreeFormComment { .. } => None,
+            _ => Some(self.ast.signature()),
+        };
 
-        from_title_tag.or_else(from_name)
+        from_title_tag.or_else(from_name).or_else(from_signature)
     }
 
-    pub fn span(&self) -> Span {
-        let start = if let Some(doc_span) = &self.doc_span {
-            doc_span.start
-        } else {
-            self.element_span.start
-        };
+    /// the reason given by the `@deprecated` tag, if there is one.
+    /// the reason may be empty.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.tag_description(TagKind::Deprecated)
+    }
 
-        let end = self.element_span.end;
+    /// the version given by the `@since` tag, if there is one
+    pub fn since(&self) -> Option<&str> {
+        self.tag_description(TagKind::Since)
+    }
 
-        start..end
+    fn tag_description(&self, kind: TagKind) -> Option<&str> {
+        self.doc
+            .iter()
+            .find(|tag| tag.kind == kind)
+            .map(|tag| tag.description.as_str())
+    }
+
+    pub fn span(&self) -> Span {
+        match &self.doc_span {
+            // trailing documentation ends after the element
+            Some(doc_span) => {
+                doc_span.start.min(self.element_span.start)..doc_span.end.max(self.element_span.end)
+            }
+            None => self.element_span.clone(),
+        }
     }
 
     pub fn raw(&self) -> &str {
@@ -162,6 +224,86 @@ impl CvlElement {
     }
 }
 
+/// the output of [parse::builder::Builder::build_with_diagnostics]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseResult {
+    pub elements: Vec<CvlElement>,
+    pub unattached_docs: Vec<UnattachedDoc>,
+    /// spans of source code that could not be parsed, with adjacent failures merged.
+    /// since only a subset of CVL is parsed, these are not necessarily errors in the spec.
+    pub parse_errors: Vec<Span>,
+}
+
+/// the output of [parse::builder::Builder::build_spec_file]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpecFile {
+    /// documentation of the file as a whole, rather than of any one element
+    pub doc: Vec<DocumentationTag>,
+    pub doc_span: Option<Span>,
+    pub imports: Vec<Import>,
+    pub usings: Vec<Using>,
+    pub elements: Vec<CvlElement>,
+    pub unattached_docs: Vec<UnattachedDoc>,
+    pub parse_errors: Vec<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Import {
+    pub imported: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Using {
+    pub contract_name: String,
+    pub spec_name: String,
+    pub span: Span,
+}
+
+impl SpecFile {
+    pub fn title(&self) -> Option<&str> {
+        self.doc
+            .iter()
+            .find(|tag| tag.kind == TagKind::Title)
+            .map(|tag| tag.description.as_str())
+    }
+}
+
+/// a documentation block that is not associated with any element,
+/// and so would otherwise be silently dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnattachedDoc {
+    pub doc: Vec<DocumentationTag>,
+    pub span: Span,
+    pub reason: UnattachedReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UnattachedReason {
+    /// the documentation is immediately followed by another documentation block,
+    /// which replaces it.
+    FollowedByDocumentation,
+    /// the documentation is followed by a freeform comment, rather than by an element.
+    FollowedByFreeForm,
+    /// the documentation is at the end of the file, with no element after it.
+    EndOfFile,
+    /// more blank lines separate the documentation from the element than allowed
+    /// by the [parse::builder::AttachmentPolicy].
+    TooManyBlankLines { found: usize, max: usize },
+    /// a plain comment separates the documentation from the element, which the
+    /// [parse::builder::AttachmentPolicy] does not allow.
+    InterveningComment,
+    /// code that failed to parse separates the documentation from the element, which the
+    /// [parse::builder::AttachmentPolicy] does not allow.
+    InterveningParseError,
+    /// trailing documentation (`///<`) that does not follow an element on the same line.
+    NoPreviousElement,
+    /// documentation at the end of a block, which is not followed by any entry of the block.
+    EndOfBlock,
+    /// documentation inside an entry of a block, such as in the body of a `preserved` block.
+    InsideEntry,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DocumentationTag {
     pub kind: TagKind,
@@ -209,6 +351,15 @@ pub enum TagKind {
     Param,
     Return,
     Formula,
+    /// copies the documentation of another element. see [inherit::inherit_docs].
+    InheritDoc,
+    Deprecated,
+    Since,
+    /// a fenced block of CVL code showing how the element is used. see [examples].
+    Example,
+    /// a requirement covered by the element. may also be written `@custom:req`.
+    /// see [requirements].
+    Req,
 }
 
 impl TagKind {
@@ -220,6 +371,11 @@ impl TagKind {
             TagKind::Param => "param",
             TagKind::Return => "return",
             TagKind::Formula => "formula",
+            TagKind::InheritDoc => "inheritdoc",
+            TagKind::Deprecated => "deprecated",
+            TagKind::Since => "since",
+            TagKind::Example => "example",
+            TagKind::Req => "req",
         }
     }
 
@@ -242,11 +398,28 @@ impl TryFrom<&str> for TagKind {
             "param" => Ok(TagKind::Param),
             "return" => Ok(TagKind::Return),
             "formula" => Ok(TagKind::Formula),
+            "inheritdoc" => Ok(TagKind::InheritDoc),
+            "deprecated" => Ok(TagKind::Deprecated),
+            "since" => Ok(TagKind::Since),
+            "example" => Ok(TagKind::Example),
+            "req" | "custom:req" => Ok(TagKind::Req),
             _ => bail!("unrecognized tag: {s}"),
         }
     }
 }
 
+impl Display for NestedKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            NestedKind::MethodsEntry => "methods",
+            NestedKind::Preserved => "preserved",
+            NestedKind::Axiom => "axiom",
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
 impl Display for Ast {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let kind = match self {
@@ -0,0 +1,258 @@
+//! inline cross references in documentation, of the form `{@link target}`,
+//! where `target` is the name of an element (`{@link totalSupplyIsSumOfBalances}`),
+//! or a contract function (`{@link Contract.function}`).
+
+use crate::util::{ByteSpan, RangeConverter, Span};
+use crate::{Ast, CvlElement, DocumentationTag};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+static LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{@link\s+([A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)?)\s*\}").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    pub target: String,
+    /// the span of the entire `{@link target}` in the source
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Segment {
+    Text(String),
+    Link(Link),
+}
+
+/// a line of a description, and where its content starts in the source
+struct DescriptionLine<'a> {
+    /// the byte offset of the line in the description
+    offset: usize,
+    content: &'a str,
+    /// the char offset of the content in the source
+    source_start: usize,
+}
+
+impl DocumentationTag {
+    /// finds each line of the description in the source of the tag. the content of a line
+    /// is what remains of the source line after the comment markers are trimmed, so it is
+    /// found by searching from the end of the source line.
+    fn description_lines<'a>(&'a self, src: &str) -> Vec<DescriptionLine<'a>> {
+        let Some(tag_src) = self.span.byte_slice(src) else {
+            return Vec::new();
+        };
+
+        let mut source_lines =
+            tag_src
+                .split_inclusive('\n')
+                .scan(self.span.start, |start, line| {
+                    let line_start = *start;
+                    *start += line.chars().count();
+                    Some((line_start, line))
+                });
+
+        let mut lines = Vec::new();
+        let mut offset = 0;
+
+        for line in self.description.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\r', '\n']);
+
+            let found = source_lines.find_map(|(line_start, source_line)| {
+                let index = source_line.rfind(content)?;
+                Some(line_start + source_line[..index].chars().count())
+            });
+            let Some(source_start) = found else {
+                break;
+            };
+
+            lines.push(DescriptionLine {
+                offset,
+                content,
+                source_start,
+            });
+            offset += line.len();
+        }
+
+        lines
+    }
+
+    /// all links in this tag. `src` is the source the tag was parsed from.
+    pub fn links(&self, src: &str) -> Vec<Link> {
+        self.segments(src)
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Segment::Link(link) => Some(link),
+                Segment::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// splits the description into text and links. `src` is the source the tag was parsed from.
+    /// a link may wrap across lines of the comment, in which case its span also covers
+    /// the comment markers between them.
+    pub fn segments(&self, src: &str) -> Vec<Segment> {
+        let lines = self.description_lines(src);
+        let to_source = |offset: usize| {
+            let Some(line) = lines.iter().rev().find(|line| line.offset <= offset) else {
+                return self.span.start;
+            };
+            let column = (offset - line.offset).min(line.content.len());
+            line.source_start + line.content[..column].chars().count()
+        };
+
+        let mut segments = Vec::new();
+        let mut last_end = 0;
+
+        for captures in LINK.captures_iter(&self.description) {
+            let entire = captures.get(0).unwrap();
+
+            if entire.start() > last_end {
+                let text = self.description[last_end..entire.start()].to_string();
+                segments.push(Segment::Text(text));
+            }
+
+            segments.push(Segment::Link(Link {
+                target: captures[1].to_string(),
+                span: to_source(entire.start())..to_source(entire.end()),
+            }));
+
+            last_end = entire.end();
+        }
+
+        if last_end < self.description.len() {
+            let text = self.description[last_end..].to_string();
+            segments.push(Segment::Text(text));
+        }
+
+        segments
+    }
+}
+
+impl CvlElement {
+    /// all links in the documentation of this element, including its nested elements
+    pub fn links(&self) -> Vec<Link> {
+        let nested_tags = self.nested.iter().flat_map(|nested| &nested.doc);
+
+        self.doc
+            .iter()
+            .chain(nested_tags)
+            .flat_map(|tag| tag.links(&self.src))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedLink<'a> {
+    pub link: Link,
+    /// the element whose documentation contains the link
+    pub source: &'a CvlElement,
+    /// what the link refers to
+    pub target: Resolution<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Resolution<'a> {
+    /// the link refers to this element
+    Element(&'a CvlElement),
+    /// the link is to `Contract.function`, where `Contract` is introduced by this `using` statement.
+    /// contract functions are not part of the spec, so whether `function` exists is not checked.
+    UncheckedFunction(&'a CvlElement),
+    /// nothing the link could refer to was found
+    Unresolved,
+}
+
+impl<'a> Resolution<'a> {
+    /// the element the link refers to, if it was resolved
+    pub fn element(self) -> Option<&'a CvlElement> {
+        match self {
+            Resolution::Element(element) => Some(element),
+            Resolution::UncheckedFunction(_) | Resolution::Unresolved => None,
+        }
+    }
+}
+
+/// resolves the links in the documentation of `elements`, against `elements` themselves,
+/// and then against the elements of each of the `imported` files.
+///
+/// a link to `Contract.function` resolves to a CVL function of that name if there is one.
+/// otherwise, if a `using` statement introduces `Contract`, either by its contract name or by
+/// its alias, the link is [Resolution::UncheckedFunction], since the function itself cannot be checked.
+pub fn resolve_links<'a>(
+    elements: &'a [CvlElement],
+    imported: &[&'a [CvlElement]],
+) -> Vec<ResolvedLink<'a>> {
+    let scopes = || std::iter::once(elements).chain(imported.iter().copied());
+
+    let find = |target: &str| {
+        let by_name = scopes()
+            .flatten()
+            .find(|element| element.ast.name() == Some(target));
+        if let Some(element) = by_name {
+            return Resolution::Element(element);
+        }
+
+        let Some((contract, _function)) = target.split_once('.') else {
+            return Resolution::Unresolved;
+        };
+        let using = scopes().flatten().find(|element| match &element.ast {
+            Ast::Using {
+                contract_name,
+                spec_name,
+            } => contract_name == contract || spec_name == contract,
+            _ => false,
+        });
+        match using {
+            Some(using) => Resolution::UncheckedFunction(using),
+            None => Resolution::Unresolved,
+        }
+    };
+
+    elements
+        .iter()
+        .flat_map(|source| {
+            source.links().into_iter().map(move |link| ResolvedLink {
+                target: find(&link.target),
+                link,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// an error for every link in `resolved` that could not be resolved,
+/// and a hint for every link to a contract function that could not be checked
+pub fn unresolved_link_diagnostics(
+    resolved: &[ResolvedLink],
+    converter: &RangeConverter,
+) -> Vec<Diagnostic> {
+    resolved
+        .iter()
+        .filter_map(|resolved| {
+            let (severity, message) = match resolved.target {
+                Resolution::Element(_) => return None,
+                Resolution::UncheckedFunction(_) => (
+                    DiagnosticSeverity::HINT,
+                    format!(
+                        "link to a contract function is not checked: {}",
+                        resolved.link.target
+                    ),
+                ),
+                Resolution::Unresolved => (
+                    DiagnosticSeverity::ERROR,
+                    format!("unresolved link: {}", resolved.link.target),
+                ),
+            };
+
+            Some(Diagnostic {
+                range: converter.to_range(resolved.link.span.clone()),
+                severity: Some(severity),
+                message,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,165 @@
+use super::{resolve_links, unresolved_link_diagnostics, Link, Resolution, Segment};
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use crate::CvlElement;
+use indoc::indoc;
+use itertools::Itertools;
+use lsp_types::DiagnosticSeverity;
+use ropey::Rope;
+
+#[test]
+fn link_segments() {
+    let src = indoc! {"
+        /// @notice see {@link solvency} and {@link  Vault.deposit }.
+        /// {@link} and {@link 1nvalid} are not links
+        rule foo { }
+    "};
+
+    let element = Builder::new(src).build().unwrap().remove(0);
+    let tag = element.doc.iter().exactly_one().unwrap();
+
+    let segments = tag.segments(src);
+    let [Segment::Text(see), Segment::Link(solvency), Segment::Text(and), Segment::Link(deposit), Segment::Text(rest)] =
+        segments.as_slice()
+    else {
+        panic!("unexpected segments: {segments:#?}")
+    };
+
+    assert_eq!(see, "see ");
+    assert_eq!(solvency.target, "solvency");
+    assert_eq!(solvency.span.byte_slice(src), Some("{@link solvency}"));
+    assert_eq!(and, " and ");
+    assert_eq!(deposit.target, "Vault.deposit");
+    assert_eq!(
+        deposit.span.byte_slice(src),
+        Some("{@link  Vault.deposit }")
+    );
+    assert_eq!(rest, ".\n{@link} and {@link 1nvalid} are not links");
+}
+
+#[test]
+fn link_resolution() {
+    let imported_src = indoc! {"
+        invariant solvency() true;
+    "};
+    let src = indoc! {"
+        using Vault as vault;
+
+        /**
+         * @notice relies on {@link solvency} and {@link helper}.
+         * @dev calls {@link vault.deposit} and {@link Vault.withdraw},
+         * but not {@link Token.transfer} or {@link missing}
+         */
+        rule foo { }
+
+        /// 🔥 see {@link foo}
+        function helper() { }
+    "};
+
+    let imported = Builder::new(imported_src).build().unwrap();
+    let elements = Builder::new(src).build().unwrap();
+    let resolved = resolve_links(&elements, &[&imported]);
+
+    let first_line = |element: &CvlElement| element.raw().lines().next().unwrap().to_string();
+    let targets = resolved
+        .iter()
+        .map(|resolved| {
+            let target = match resolved.target {
+                Resolution::Element(element) => format!("element: {}", first_line(element)),
+                Resolution::UncheckedFunction(using) => format!("unchecked: {}", first_line(using)),
+                Resolution::Unresolved => "unresolved".to_string(),
+            };
+            format!("{} -> {target}", resolved.link.target)
+        })
+        .collect_vec();
+    assert_eq!(
+        targets,
+        [
+            "solvency -> element: invariant solvency() true;",
+            "helper -> element: /// 🔥 see {@link foo}",
+            "vault.deposit -> unchecked: using Vault as vault;",
+            "Vault.withdraw -> unchecked: using Vault as vault;",
+            "Token.transfer -> unresolved",
+            "missing -> unresolved",
+            "foo -> element: /**",
+        ]
+    );
+    assert!(resolved[2].target.element().is_none());
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostics = unresolved_link_diagnostics(&resolved, &converter);
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.severity.unwrap(), diagnostic.message.as_str()))
+        .collect_vec();
+    assert_eq!(
+        messages,
+        [
+            (
+                DiagnosticSeverity::HINT,
+                "link to a contract function is not checked: vault.deposit"
+            ),
+            (
+                DiagnosticSeverity::HINT,
+                "link to a contract function is not checked: Vault.withdraw"
+            ),
+            (DiagnosticSeverity::ERROR, "unresolved link: Token.transfer"),
+            (DiagnosticSeverity::ERROR, "unresolved link: missing"),
+        ]
+    );
+
+    let Link { span, .. } = &resolved[4].link;
+    assert_eq!(converter.to_range(span.clone()).start.line, 5);
+    assert_eq!(span.byte_slice(src), Some("{@link Token.transfer}"));
+}
+
+#[test]
+fn wrapped_links() {
+    let src = indoc! {"
+        /**
+         * @notice see {@link
+         *   missing} and then {@link foo}, {@link
+         * other}
+         */
+        rule foo { }
+    "};
+
+    let elements = Builder::new(src).build().unwrap();
+    let tag = elements[0].doc.iter().exactly_one().unwrap();
+
+    let links = tag
+        .segments(src)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Link(link) => Some(link),
+            Segment::Text(_) => None,
+        })
+        .collect_vec();
+    let found = links
+        .iter()
+        .map(|link| (link.target.as_str(), link.span.byte_slice(src).unwrap()))
+        .collect_vec();
+    assert_eq!(
+        found,
+        [
+            ("missing", "{@link\n *   missing}"),
+            ("foo", "{@link foo}"),
+            ("other", "{@link\n * other}"),
+        ]
+    );
+    assert_eq!(tag.links(src), links);
+
+    let resolved = resolve_links(&elements, &[]);
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostics = unresolved_link_diagnostics(&resolved, &converter)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.message, diagnostic.range.start.line))
+        .collect_vec();
+    assert_eq!(
+        diagnostics,
+        [
+            ("unresolved link: missing".to_string(), 1),
+            ("unresolved link: other".to_string(), 2),
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+//! helpers for migrating specs that predate `CVLDoc`.
+
+use crate::parse::builder::{Builder, ContentLines};
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, TagKind};
+use color_eyre::Result;
+use itertools::Itertools;
+use lsp_types::TextEdit;
+use ropey::Rope;
+
+#[cfg(test)]
+mod tests;
+
+/// finds every undocumented element that accepts a `@notice` tag and has a block of plain comments
+/// directly above it (see [Builder::with_plain_comment_docs]), and returns edits that rewrite each such block
+/// into slashed `CVLDoc` documentation.
+pub fn plain_comments_to_cvldoc(src: &str) -> Result<Vec<TextEdit>> {
+    let builder = Builder::new(src);
+    let (_, comments) = builder.lex_with_comments()?;
+    let elements = Builder::new(src).build()?;
+
+    let rope = Rope::from_str(src);
+    let converter = RangeConverter::new(rope.clone());
+
+    let edits = elements
+        .iter()
+        .filter(|element| element.doc_span.is_none())
+        .filter(|element| !matches!(element.ast, Ast::FreeFormComment { .. }))
+        .filter(|element| element.ast.supports(&TagKind::Notice))
+        .filter_map(|element| builder.plain_comment_block(&rope, &element.element_span, &comments))
+        .filter_map(|block| {
+            let block = without_line_terminator(&rope, block);
+            let new_text = as_slashed_doc(&rope, block.clone());
+
+            // comments with no text are left as they are
+            let edit = TextEdit {
+                range: converter.to_range(block),
+                new_text,
+            };
+            Some(edit).filter(|edit| !edit.new_text.is_empty())
+        })
+        .collect();
+
+    Ok(edits)
+}
+
+fn without_line_terminator(rope: &Rope, mut span: Span) -> Span {
+    while span.end > span.start && matches!(rope.char(span.end - 1), '\n' | '\r') {
+        span.end -= 1;
+    }
+    span
+}
+
+fn as_slashed_doc(rope: &Rope, block: Span) -> String {
+    let line_start = rope.line_to_char(rope.char_to_line(block.start));
+    let indent = rope.slice(line_start..block.start).to_string();
+
+    let text = rope.slice(block.clone()).to_string();
+    let contents = ContentLines::new(&text, block, &['/', '*'])
+        .map(|(line, _)| line.content)
+        .collect_vec();
+
+    let first = contents.iter().position(|line| !line.is_empty());
+    let last = contents.iter().rposition(|line| !line.is_empty());
+    let contents = match (first, last) {
+        (Some(first), Some(last)) => &contents[first..=last],
+        _ => &[],
+    };
+
+    contents
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                "///".to_string()
+            } else {
+                format!("/// {line}")
+            }
+        })
+        .join(&format!("\n{indent}"))
+}
@@ -0,0 +1,153 @@
+use super::plain_comments_to_cvldoc;
+use crate::parse::builder::{AttachmentPolicy, Builder};
+use crate::{TagKind, UnattachedReason};
+use indoc::indoc;
+use itertools::Itertools;
+use lsp_types::{Position, Range};
+
+const LEGACY_SPEC: &str = indoc! {"
+    methods {
+        function balanceOf(address) external returns (uint256) envfree;
+    }
+
+    // Transfers must not change the total supply.
+    // @dev checked for all methods
+    rule transferPreservesSupply(method f) { }
+
+    /* An account's balance never exceeds the total supply. */
+    invariant balanceBelowSupply(address a)
+        balanceOf(a) <= totalSupply();
+
+    // this comment is separated by a blank line
+
+    rule undocumented { }
+
+    rule trailing { } // trailing comments are not documentation
+    rule afterTrailing { }
+
+    /// already documented
+    // so this is ignored
+    rule documented { }
+"};
+
+#[test]
+fn plain_comments_as_documentation() {
+    let elements = Builder::new(LEGACY_SPEC)
+        .with_plain_comment_docs(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(elements.len(), 7);
+
+    let [notice, dev] = elements[1].doc.as_slice() else {
+        panic!("expected exactly 2 tags")
+    };
+    assert_eq!(notice.kind, TagKind::Notice);
+    assert_eq!(
+        notice.description,
+        "Transfers must not change the total supply."
+    );
+    assert_eq!(dev.kind, TagKind::Dev);
+    assert_eq!(dev.description, "checked for all methods");
+
+    let invariant_notice = elements[2].doc.iter().exactly_one().unwrap();
+    assert_eq!(
+        invariant_notice.description,
+        "An account's balance never exceeds the total supply."
+    );
+
+    // separated by a blank line, or not on a line of its own
+    for undocumented in &elements[3..=5] {
+        assert!(undocumented.doc.is_empty());
+    }
+
+    let documented = elements[6].doc.iter().exactly_one().unwrap();
+    assert_eq!(documented.description, "already documented");
+
+    let without_fallback = Builder::new(LEGACY_SPEC).build().unwrap();
+    assert!(without_fallback[1].doc.is_empty());
+}
+
+#[test]
+fn migration_edits() {
+    let edits = plain_comments_to_cvldoc(LEGACY_SPEC).unwrap();
+
+    let [rule_edit, invariant_edit] = edits.as_slice() else {
+        panic!("expected exactly 2 edits, got {edits:#?}")
+    };
+
+    assert_eq!(
+        rule_edit.range,
+        Range::new(Position::new(4, 0), Position::new(5, 31))
+    );
+    assert_eq!(
+        rule_edit.new_text,
+        "/// Transfers must not change the total supply.\n/// @dev checked for all methods"
+    );
+
+    assert_eq!(
+        invariant_edit.range,
+        Range::new(Position::new(8, 0), Position::new(8, 58))
+    );
+    assert_eq!(
+        invariant_edit.new_text,
+        "/// An account's balance never exceeds the total supply."
+    );
+}
+
+const WITHOUT_NOTICE: &str = indoc! {"
+    // the token under verification
+    using ERC20 as token;
+
+    // setup hooks
+    hook Sstore _balances[KEY address a] uint v (uint oldValue) STORAGE { }
+
+    // the supply never decreases
+    rule supplyMonotone { }
+"};
+
+#[test]
+fn elements_without_notice_are_not_documented() {
+    let elements = Builder::new(WITHOUT_NOTICE)
+        .with_plain_comment_docs(true)
+        .build()
+        .unwrap();
+
+    let [using, hook, rule] = elements.as_slice() else {
+        panic!("expected exactly 3 elements, got {elements:#?}")
+    };
+    assert!(using.doc.is_empty());
+    assert!(hook.doc.is_empty());
+    let notice = rule.doc.iter().exactly_one().unwrap();
+    assert_eq!(notice.description, "the supply never decreases");
+
+    let edits = plain_comments_to_cvldoc(WITHOUT_NOTICE).unwrap();
+    let edit = edits.iter().exactly_one().unwrap();
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(6, 0), Position::new(6, 29))
+    );
+    assert_eq!(edit.new_text, "/// the supply never decreases");
+}
+
+#[test]
+fn rejected_documentation_is_not_replaced_by_plain_comments() {
+    let src = indoc! {"
+        /// the supply never decreases
+        // TODO: also check burns
+        rule supplyMonotone { }
+    "};
+
+    let parse_result = Builder::new(src)
+        .with_attachment_policy(AttachmentPolicy::strict(0))
+        .with_plain_comment_docs(true)
+        .build_with_diagnostics()
+        .unwrap();
+
+    let rule = parse_result.elements.iter().exactly_one().unwrap();
+    assert!(rule.doc.is_empty());
+    assert!(rule.doc_span.is_none());
+
+    let unattached = parse_result.unattached_docs.iter().exactly_one().unwrap();
+    assert_eq!(unattached.reason, UnattachedReason::InterveningComment);
+}
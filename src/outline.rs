@@ -0,0 +1,111 @@
+//! groups elements into sections, according to the Markdown headings
+//! (`#`, `##`, ...) written in freeform comments.
+
+use crate::util::Span;
+use crate::{Ast, CvlElement};
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Section {
+    /// the number of `#`s in the heading. the root section has level 0.
+    pub level: usize,
+    pub title: String,
+    /// the text that follows the heading in the freeform comment, if any
+    pub text: String,
+    /// the span of the freeform comment containing the heading. `None` for the root section.
+    pub span: Option<Span>,
+    pub elements: Vec<CvlElement>,
+    pub subsections: Vec<Section>,
+}
+
+impl Section {
+    fn new(level: usize, title: String, span: Option<Span>) -> Section {
+        Section {
+            level,
+            title,
+            text: String::new(),
+            span,
+            elements: Vec::new(),
+            subsections: Vec::new(),
+        }
+    }
+
+    fn push_text(&mut self, line: &str) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(line);
+    }
+
+    fn trim_text(&mut self) {
+        self.text = self.text.trim().to_string();
+    }
+}
+
+/// parses a Markdown ATX heading, returning its level and title
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+
+    // as in CommonMark, a closing sequence of `#` is only removed if it follows a space
+    let title = rest.trim();
+    let without_closing = title.trim_end_matches('#');
+    let title = match without_closing.is_empty() || without_closing.ends_with(char::is_whitespace) {
+        true => without_closing.trim_end(),
+        false => title,
+    };
+    Some((level, title))
+}
+
+/// builds a tree of sections out of the headings in freeform comments.
+/// elements that appear before the first heading belong to the root section.
+/// freeform comments that contain no headings are kept as elements of the current section.
+pub fn outline(elements: &[CvlElement]) -> Section {
+    let mut stack = vec![Section::new(0, String::new(), None)];
+
+    for element in elements {
+        let Ast::FreeFormComment { text } = &element.ast else {
+            stack.last_mut().unwrap().elements.push(element.clone());
+            continue;
+        };
+
+        if !text.lines().any(|line| heading(line).is_some()) {
+            stack.last_mut().unwrap().elements.push(element.clone());
+            continue;
+        }
+
+        for line in text.lines() {
+            if let Some((level, title)) = heading(line) {
+                close_sections(&mut stack, level);
+
+                let span = Some(element.element_span.clone());
+                stack.push(Section::new(level, title.to_string(), span));
+            } else {
+                stack.last_mut().unwrap().push_text(line);
+            }
+        }
+    }
+
+    close_sections(&mut stack, 1);
+
+    let mut root = stack.pop().unwrap();
+    root.trim_text();
+    root
+}
+
+/// closes all open sections whose level is at least `level`, attaching each to its parent
+fn close_sections(stack: &mut Vec<Section>, level: usize) {
+    while stack.len() > 1 && stack.last().is_some_and(|section| section.level >= level) {
+        let mut section = stack.pop().unwrap();
+        section.trim_text();
+        stack.last_mut().unwrap().subsections.push(section);
+    }
+}
@@ -0,0 +1,90 @@
+use super::{heading, outline};
+use crate::parse::builder::Builder;
+use indoc::indoc;
+use itertools::Itertools;
+
+#[test]
+fn sections_from_headings() {
+    let src = indoc! {"
+        methods {
+            function totalSupply() external returns (uint256) envfree;
+        }
+
+        //// # ERC20 Specification
+        //// Properties of the token.
+
+        /***
+         * ## Invariants
+         */
+
+        invariant solvency() totalSupply() >= 0;
+
+        /// documented
+        invariant nonNegative() totalSupply() >= 0;
+
+        /**** ## Rules ****/
+
+        //// just a remark, not a heading
+
+        rule transfer { }
+
+        /***
+         * ### Helpers ###
+         * some helper definitions
+         * # Appendix
+         */
+        definition MAX() returns uint = 1;
+    "};
+
+    let elements = Builder::new(src).build().unwrap();
+    let root = outline(&elements);
+
+    assert_eq!(root.level, 0);
+    assert!(root.span.is_none());
+    assert_eq!(root.elements.len(), 1);
+
+    let [erc20, appendix] = root.subsections.as_slice() else {
+        panic!("expected exactly 2 top level sections")
+    };
+    assert_eq!(erc20.title, "ERC20 Specification");
+    assert_eq!(erc20.text, "Properties of the token.");
+    assert!(erc20.elements.is_empty());
+
+    let [invariants, rules] = erc20.subsections.as_slice() else {
+        panic!("expected exactly 2 subsections")
+    };
+    assert_eq!(invariants.title, "Invariants");
+    let invariant_names = invariants
+        .elements
+        .iter()
+        .map(|element| element.ast.name().unwrap())
+        .collect_vec();
+    assert_eq!(invariant_names, ["solvency", "nonNegative"]);
+
+    assert_eq!(rules.level, 2);
+    assert_eq!(rules.elements.len(), 2, "the remark is kept as an element");
+
+    let helpers = rules.subsections.iter().exactly_one().unwrap();
+    assert_eq!(helpers.level, 3);
+    assert_eq!(helpers.title, "Helpers");
+    assert_eq!(helpers.text, "some helper definitions");
+    assert!(helpers.elements.is_empty());
+
+    assert_eq!(appendix.title, "Appendix");
+    assert_eq!(appendix.elements[0].ast.name(), Some("MAX"));
+}
+
+#[test]
+fn closing_sequences() {
+    assert_eq!(heading("# Title"), Some((1, "Title")));
+    assert_eq!(heading("## Title ##"), Some((2, "Title")));
+    assert_eq!(heading("### Title #####   "), Some((3, "Title")));
+    assert_eq!(heading("# C#"), Some((1, "C#")));
+    assert_eq!(heading("# Title#"), Some((1, "Title#")));
+    assert_eq!(heading("# Title \\#"), Some((1, "Title \\#")));
+    assert_eq!(heading("## ###"), Some((2, "")));
+    assert_eq!(heading("#"), Some((1, "")));
+
+    assert_eq!(heading("#Title"), None);
+    assert_eq!(heading("####### Title"), None);
+}
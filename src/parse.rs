@@ -277,6 +277,7 @@ fn cvl_parser() -> impl Parser<Token, Vec<(Intermediate, Span)>, Error = Simple<
     let cvl_doc = select! {
         Token::CvlDocSlashed => Style::Slashed,
         Token::CvlDocStarred => Style::Starred,
+        Token::CvlDocTrailing => Style::Trailing,
     }
     .map_with_span(Intermediate::Documentation)
     .labelled("documentation");
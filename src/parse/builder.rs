@@ -1,14 +1,20 @@
 use super::terminated_str::TerminatedStr;
 use super::types::Token;
 use super::{cvl_parser, lexer::cvl_lexer, Intermediate, Span, Style};
-use crate::util::ByteSpan;
-use crate::{Ast, CvlElement, DocumentationTag, TagKind};
+use crate::util::{ByteSpan, Spanned};
+use crate::{
+    Ast, CvlElement, DocumentationTag, Import, NestedElement, NestedKind, ParseResult, SpecFile,
+    TagKind, UnattachedDoc, UnattachedReason, Using,
+};
 use chumsky::{Parser, Stream};
 use color_eyre::eyre::{bail, eyre};
 use color_eyre::Result;
 use core::panic;
+use ropey::Rope;
 use std::sync::Arc;
 
+const TRAILING_DOC_PREFIX: &str = "///<";
+
 struct DocumentationBuilder<'src> {
     kind: TagKind,
     desc: Vec<TerminatedStr<'src>>,
@@ -40,9 +46,13 @@ impl DocumentationTag {
                     tags.push(builder.build_current());
                 }
 
-                if let Some(after_tag) = line.content.get(new_tag.len() + 1..) {
-                    line.content = after_tag;
-                }
+                // the tag may be the only thing on the line. the tag is matched by
+                // its full text, since it may be spelled in more than one way.
+                let tag_end = line
+                    .content
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(line.content.len());
+                line.content = line.content[tag_end..].trim_start();
 
                 builder.kind = new_tag;
 
@@ -85,28 +95,154 @@ impl<'a> DocumentationBuilder<'a> {
 
 enum DocOrAst {
     Doc(Vec<DocumentationTag>),
+    TrailingDoc(Vec<DocumentationTag>),
     Ast(Ast),
 }
 
-pub struct Builder<'src>(&'src str);
+/// extends the span of the documentation of an element to cover trailing documentation
+fn extend_doc_span(doc_span: &mut Option<Span>, trailing: &Span) {
+    *doc_span = Some(match doc_span.take() {
+        Some(doc_span) => doc_span.start.min(trailing.start)..doc_span.end.max(trailing.end),
+        None => trailing.clone(),
+    });
+}
+
+fn unattach(
+    unattached_docs: &mut Vec<UnattachedDoc>,
+    current_doc: &mut Option<(Vec<DocumentationTag>, Span)>,
+    reason: UnattachedReason,
+) {
+    if let Some((doc, span)) = current_doc.take() {
+        unattached_docs.push(UnattachedDoc { doc, span, reason });
+    }
+}
+
+fn blank_lines_between(rope: &Rope, doc_span: &Span, element_span: &Span) -> usize {
+    // the last char of the doc span may be its terminating newline,
+    // which is still on the same line as the doc itself.
+    let doc_end_line = rope.char_to_line(doc_span.end.saturating_sub(1));
+    let element_line = rope.char_to_line(element_span.start);
+
+    (doc_end_line + 1..element_line)
+        .filter(|&line| rope.line(line).chars().all(char::is_whitespace))
+        .count()
+}
+
+/// checks whether `comment` is on its own line(s), and is immediately followed
+/// by whatever starts at `next_start` on the next line.
+fn directly_above(rope: &Rope, comment: &Span, next_start: usize) -> bool {
+    let only_whitespace = |span: Span| rope.slice(span).chars().all(char::is_whitespace);
+
+    let comment_line = rope.char_to_line(comment.start);
+    let comment_end_line = rope.char_to_line(comment.end.saturating_sub(1));
+    let line_start = rope.line_to_char(comment_line);
+
+    only_whitespace(line_start..comment.start)
+        && only_whitespace(comment.end..next_start)
+        && comment_end_line + 1 == rope.char_to_line(next_start)
+}
+
+/// checks whether trailing documentation starts on the line where `element_span` ends
+fn on_same_line(rope: &Rope, element_span: &Span, trailing_span: &Span) -> bool {
+    let element_end_line = rope.char_to_line(element_span.end.saturating_sub(1));
+    element_end_line == rope.char_to_line(trailing_span.start)
+}
+
+/// controls when a documentation block is associated with the element that follows it.
+///
+/// the default policy is permissive: documentation is attached to the next element,
+/// no matter what comes in between. trailing documentation is attached to the previous element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentPolicy {
+    /// if set, documentation is attached only if at most this many blank lines
+    /// separate it from the element.
+    pub max_blank_lines: Option<usize>,
+    /// whether plain (non-documentation) comments may separate the documentation from the element.
+    pub allow_plain_comments: bool,
+    /// whether code that failed to parse may separate the documentation from the element.
+    pub allow_parse_errors: bool,
+    /// whether trailing documentation (`///<`) is attached to the element that precedes it
+    /// on the same line, rather than to the element that follows it.
+    pub attach_trailing_to_previous: bool,
+}
+
+impl Default for AttachmentPolicy {
+    fn default() -> Self {
+        AttachmentPolicy {
+            max_blank_lines: None,
+            allow_plain_comments: true,
+            allow_parse_errors: true,
+            attach_trailing_to_previous: true,
+        }
+    }
+}
+
+impl AttachmentPolicy {
+    /// only attach documentation that is directly adjacent to its element,
+    /// up to `max_blank_lines` blank lines.
+    pub fn strict(max_blank_lines: usize) -> AttachmentPolicy {
+        AttachmentPolicy {
+            max_blank_lines: Some(max_blank_lines),
+            allow_plain_comments: false,
+            allow_parse_errors: false,
+            attach_trailing_to_previous: true,
+        }
+    }
+}
+
+pub struct Builder<'src> {
+    src: &'src str,
+    attachment_policy: AttachmentPolicy,
+    plain_comment_docs: bool,
+}
 
 impl<'src> Builder<'src> {
     pub fn new(src: &'src str) -> Self {
-        Builder(src)
+        Builder {
+            src,
+            attachment_policy: AttachmentPolicy::default(),
+            plain_comment_docs: false,
+        }
+    }
+
+    pub fn with_attachment_policy(mut self, attachment_policy: AttachmentPolicy) -> Self {
+        self.attachment_policy = attachment_policy;
+        self
+    }
+
+    /// if enabled, an element without documentation is documented by the block of
+    /// plain comments directly above it, if there is one. this is useful for older specs,
+    /// which were not written with `CVLDoc` in mind.
+    ///
+    /// only elements that accept a `@notice` tag are documented this way, and never an element
+    /// whose own documentation was rejected by the [AttachmentPolicy].
+    pub fn with_plain_comment_docs(mut self, enabled: bool) -> Self {
+        self.plain_comment_docs = enabled;
+        self
     }
 
     pub fn lex(&self) -> Result<Vec<(Token, Span)>> {
-        let mut lexed = cvl_lexer()
-            .parse(self.0)
+        let (lexed, _comments) = self.lex_with_comments()?;
+        Ok(lexed)
+    }
+
+    /// lexes the source, and separates out the spans of plain comments
+    pub(crate) fn lex_with_comments(&self) -> Result<(Vec<Spanned<Token>>, Vec<Span>)> {
+        let lexed = cvl_lexer()
+            .parse(self.src)
             .map_err(|_| eyre!("lexing failed"))?;
-        lexed.retain(|(tok, _)| !matches!(tok, Token::SingleLineComment | Token::MultiLineComment));
 
-        Ok(lexed)
+        let (comments, lexed): (Vec<_>, Vec<_>) = lexed.into_iter().partition(|(tok, _)| {
+            matches!(tok, Token::SingleLineComment | Token::MultiLineComment)
+        });
+        let comments = comments.into_iter().map(|(_, span)| span).collect();
+
+        Ok((lexed, comments))
     }
 
     fn parse(&self, lexed: Vec<(Token, Span)>) -> Result<Vec<(Intermediate, Span)>> {
         let end_span = {
-            let len = self.0.chars().count();
+            let len = self.src.chars().count();
             len..len + 1
         };
         let stream = Stream::from_iter(end_span, lexed.into_iter());
@@ -115,22 +251,137 @@ impl<'src> Builder<'src> {
     }
 
     pub fn build(self) -> Result<Vec<CvlElement>> {
-        let lexed = self.lex().unwrap();
-        let parsed = self.parse(lexed).unwrap();
-        self.output_cvl_elements(parsed)
+        self.build_with_diagnostics()
+            .map(|parse_result| parse_result.elements)
+    }
+
+    /// like [Builder::build], but also returns any documentation
+    /// that could not be attached to an element.
+    pub fn build_with_diagnostics(self) -> Result<ParseResult> {
+        let (lexed, comments) = self.lex_with_comments().unwrap();
+        let parsed = self.parse(lexed.clone()).unwrap();
+        Ok(self.output_cvl_elements(parsed, &lexed, &comments))
+    }
+
+    /// like [Builder::build_with_diagnostics], but also separates out the documentation
+    /// of the file itself, along with its imports and `using` statements.
+    ///
+    /// the file's documentation is the first documentation block before the first declaration
+    /// such that either:
+    /// - it precedes an `import` or `using` statement, or
+    /// - it contains a `@title` tag, and is followed by a blank line.
+    pub fn build_spec_file(self) -> Result<SpecFile> {
+        let src = self.src;
+        let mut parse_result = self.build_with_diagnostics()?;
+        let rope = Rope::from_str(src);
+
+        let first_decl = parse_result
+            .elements
+            .iter()
+            .position(|element| !matches!(element.ast, Ast::FreeFormComment { .. }));
+        let boundary = match first_decl {
+            Some(i) => parse_result.elements[i].span().start,
+            None => rope.len_chars(),
+        };
+
+        let has_title = |doc: &[DocumentationTag]| doc.iter().any(|tag| tag.kind == TagKind::Title);
+        let precedes_import = first_decl.is_some_and(|i| {
+            matches!(
+                parse_result.elements[i].ast,
+                Ast::Import { .. } | Ast::Using { .. }
+            )
+        });
+        let is_file_doc = |doc: &[DocumentationTag], doc_span: &Span, next_start: usize| {
+            precedes_import
+                || (has_title(doc)
+                    && blank_lines_between(&rope, doc_span, &(next_start..next_start)) > 0)
+        };
+
+        // an unattached doc may be followed by a freeform comment, rather than by the first element
+        let leading_unattached = parse_result
+            .unattached_docs
+            .iter()
+            .take_while(|unattached| unattached.span.end <= boundary)
+            .position(|unattached| {
+                let span = &unattached.span;
+                let after = rope.slice(span.end..).chars();
+                let next_start = span.end + after.take_while(|c| c.is_whitespace()).count();
+                is_file_doc(&unattached.doc, span, next_start)
+            });
+
+        let (doc, doc_span) = if let Some(i) = leading_unattached {
+            let unattached = parse_result.unattached_docs.remove(i);
+            (unattached.doc, Some(unattached.span))
+        } else if let Some(element) = first_decl.map(|i| &mut parse_result.elements[i]) {
+            let attached_file_doc = match &element.doc_span {
+                Some(doc_span) => is_file_doc(&element.doc, doc_span, element.element_span.start),
+                None => false,
+            };
+
+            if attached_file_doc {
+                (std::mem::take(&mut element.doc), element.doc_span.take())
+            } else {
+                (Vec::new(), None)
+            }
+        } else {
+            (Vec::new(), None)
+        };
+
+        let ParseResult {
+            elements,
+            unattached_docs,
+            parse_errors,
+        } = parse_result;
+
+        let imports = elements
+            .iter()
+            .filter_map(|element| match &element.ast {
+                Ast::Import { imported } => Some(Import {
+                    imported: imported.clone(),
+                    span: element.element_span.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+        let usings = elements
+            .iter()
+            .filter_map(|element| match &element.ast {
+                Ast::Using {
+                    contract_name,
+                    spec_name,
+                } => Some(Using {
+                    contract_name: contract_name.clone(),
+                    spec_name: spec_name.clone(),
+                    span: element.element_span.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(SpecFile {
+            doc,
+            doc_span,
+            imports,
+            usings,
+            elements,
+            unattached_docs,
+            parse_errors,
+        })
     }
 
     const fn chars_to_trim<'a>(style: Style) -> &'a [char] {
         match style {
             Style::Slashed => &['/'],
             Style::Starred => &['/', '*'],
+            // the exact prefix is stripped instead, see [ContentLines::with_prefix]
+            Style::Trailing => &[],
         }
     }
 
     //this panics, because a failure is an unrecoverable logic error
     fn slice(&self, s: impl Into<Span>) -> &str {
         let span: Span = s.into();
-        span.byte_slice(self.0)
+        span.byte_slice(self.src)
             .unwrap_or_else(|| panic!("{:?}: not in source bounds", span))
     }
 
@@ -153,35 +404,97 @@ impl<'src> Builder<'src> {
     fn output_cvl_elements(
         &self,
         parsing_results: Vec<(Intermediate, Span)>,
-    ) -> Result<Vec<CvlElement>> {
-        let src_ref = Arc::from(self.0);
-
-        let mut elements = Vec::new();
-        let mut current_doc: Option<Vec<DocumentationTag>> = None;
-        let mut current_doc_span: Option<Span> = None;
+        tokens: &[Spanned<Token>],
+        comments: &[Span],
+    ) -> ParseResult {
+        let src_ref = Arc::from(self.src);
+        let rope = Rope::from_str(self.src);
+        let policy = &self.attachment_policy;
+
+        let mut elements: Vec<CvlElement> = Vec::new();
+        let mut unattached_docs = Vec::new();
+        let mut parse_errors: Vec<Span> = Vec::new();
+        let mut current_doc: Option<(Vec<DocumentationTag>, Span)> = None;
 
         for parse_result in parsing_results {
+            if matches!(parse_result.0, Intermediate::ParseError) {
+                let span = &parse_result.1;
+                match parse_errors.last_mut() {
+                    Some(last)
+                        if last.end == span.start
+                            || self.slice(last.end..span.start).trim().is_empty() =>
+                    {
+                        last.end = span.end;
+                    }
+                    _ => parse_errors.push(span.clone()),
+                }
+
+                if !policy.allow_parse_errors {
+                    let reason = UnattachedReason::InterveningParseError;
+                    unattach(&mut unattached_docs, &mut current_doc, reason);
+                }
+                continue;
+            }
+
+            let nested_block = parse_result.0.nested_block();
+
             let Ok((doc_or_ast, span)) = self.process_intermediate(parse_result) else {
                 continue;
             };
 
             match doc_or_ast {
                 DocOrAst::Ast(ast @ Ast::FreeFormComment { .. }) => {
-                    // assert!(current_doc.is_none(), "documentation followed by freeform");
+                    let reason = UnattachedReason::FollowedByFreeForm;
+                    unattach(&mut unattached_docs, &mut current_doc, reason);
+
                     elements.push(CvlElement {
                         doc: Vec::new(),
                         ast,
                         element_span: span,
                         doc_span: None,
+                        nested: Vec::new(),
                         src: Arc::clone(&src_ref),
                     });
                 }
                 DocOrAst::Ast(ast) => {
-                    let (doc, doc_span) = match (current_doc.take(), current_doc_span.take()) {
-                        (Some(doc), Some(doc_span)) => (doc, Some(doc_span)),
-                        (None, None) => (Vec::new(), None),
-                        (Some(_), None) => panic!("got doc without doc_span"),
-                        (None, Some(_)) => panic!("got doc_span without doc"),
+                    let mut rejected = false;
+                    if let Some((_, doc_span)) = &current_doc {
+                        if let Some(reason) =
+                            self.attachment_violation(&rope, doc_span, &span, comments)
+                        {
+                            unattach(&mut unattached_docs, &mut current_doc, reason);
+                            rejected = true;
+                        }
+                    }
+
+                    // plain comments do not stand in for documentation that the policy rejected
+                    let fallback_doc = || {
+                        if !self.plain_comment_docs || rejected || !ast.supports(&TagKind::Notice) {
+                            return None;
+                        }
+                        let block = self.plain_comment_block(&rope, &span, comments)?;
+                        let doc = self.documentation(Style::Starred, block.clone());
+                        Some((doc, block))
+                    };
+
+                    let (doc, doc_span) = match current_doc.take().or_else(fallback_doc) {
+                        Some((doc, doc_span)) => (doc, Some(doc_span)),
+                        None => (Vec::new(), None),
+                    };
+
+                    let nested = match nested_block {
+                        Some((kind, block)) => {
+                            let unattached_docs = &mut unattached_docs;
+                            self.nested_elements(
+                                kind,
+                                &block,
+                                tokens,
+                                comments,
+                                &rope,
+                                unattached_docs,
+                            )
+                        }
+                        None => Vec::new(),
                     };
 
                     elements.push(CvlElement {
@@ -189,22 +502,224 @@ impl<'src> Builder<'src> {
                         ast,
                         element_span: span,
                         doc_span,
+                        nested,
                         src: Arc::clone(&src_ref),
                     });
                 }
-                DocOrAst::Doc(doc) => {
-                    // assert!(
-                    //     current_doc.is_none(),
-                    //     "documentation followed by documentation"
-                    // );
-                    current_doc = Some(doc);
-                    current_doc_span = Some(span);
-                    continue;
+                DocOrAst::TrailingDoc(doc) if policy.attach_trailing_to_previous => {
+                    let previous = elements.last_mut().filter(|element| {
+                        !matches!(element.ast, Ast::FreeFormComment { .. })
+                            && on_same_line(&rope, &element.element_span, &span)
+                    });
+
+                    if let Some(previous) = previous {
+                        previous.doc.extend(doc);
+                        extend_doc_span(&mut previous.doc_span, &span);
+                    } else {
+                        let reason = UnattachedReason::NoPreviousElement;
+                        unattach(&mut unattached_docs, &mut Some((doc, span)), reason);
+                    }
+                }
+                DocOrAst::Doc(doc) | DocOrAst::TrailingDoc(doc) => {
+                    let reason = UnattachedReason::FollowedByDocumentation;
+                    unattach(&mut unattached_docs, &mut current_doc, reason);
+                    current_doc = Some((doc, span));
                 }
             }
         }
 
-        Ok(elements)
+        let reason = UnattachedReason::EndOfFile;
+        unattach(&mut unattached_docs, &mut current_doc, reason);
+
+        ParseResult {
+            elements,
+            unattached_docs,
+            parse_errors,
+        }
+    }
+
+    /// splits the contents of a block into its entries, where an entry is terminated
+    /// either by a semicolon or by a closing curly bracket.
+    /// documentation inside the block is attached to the entries, as it would be at the top level.
+    fn nested_elements(
+        &self,
+        kind: NestedKind,
+        block: &Span,
+        tokens: &[Spanned<Token>],
+        comments: &[Span],
+        rope: &Rope,
+        unattached_docs: &mut Vec<UnattachedDoc>,
+    ) -> Vec<NestedElement> {
+        let policy = &self.attachment_policy;
+
+        //the curly brackets delimiting the block itself are excluded
+        let inner_tokens = tokens
+            .iter()
+            .filter(|(_, span)| block.start < span.start && span.end < block.end);
+
+        let mut entries: Vec<NestedElement> = Vec::new();
+        let mut entry: Option<NestedElement> = None;
+        let mut current_doc: Option<(Vec<DocumentationTag>, Span)> = None;
+        let mut depth = 0usize;
+
+        for (token, span) in inner_tokens {
+            match token {
+                Token::CvlDocTrailing if policy.attach_trailing_to_previous => {
+                    let doc = self.documentation(Style::Trailing, span.clone());
+
+                    let previous = entries.last_mut().filter(|previous| {
+                        entry.is_none() && on_same_line(rope, &previous.span, span)
+                    });
+
+                    if let Some(previous) = previous {
+                        previous.doc.extend(doc);
+                        extend_doc_span(&mut previous.doc_span, span);
+                    } else {
+                        let reason = UnattachedReason::NoPreviousElement;
+                        unattach(unattached_docs, &mut Some((doc, span.clone())), reason);
+                    }
+                }
+                Token::CvlDocSlashed | Token::CvlDocStarred | Token::CvlDocTrailing => {
+                    let style = match token {
+                        Token::CvlDocSlashed => Style::Slashed,
+                        Token::CvlDocStarred => Style::Starred,
+                        _ => Style::Trailing,
+                    };
+                    let doc = self.documentation(style, span.clone());
+
+                    if depth > 0 || entry.is_some() {
+                        // documentation inside an entry does not document the next entry
+                        let reason = UnattachedReason::InsideEntry;
+                        unattach(unattached_docs, &mut Some((doc, span.clone())), reason);
+                        continue;
+                    }
+
+                    let reason = UnattachedReason::FollowedByDocumentation;
+                    unattach(unattached_docs, &mut current_doc, reason);
+                    current_doc = Some((doc, span.clone()));
+                }
+                Token::FreeFormSlashed | Token::FreeFormStarred => {
+                    let reason = UnattachedReason::FollowedByFreeForm;
+                    unattach(unattached_docs, &mut current_doc, reason);
+                }
+                _ => {
+                    match token {
+                        Token::CurlyOpen => depth += 1,
+                        Token::CurlyClose => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+
+                    let mut current = entry.take().unwrap_or_else(|| {
+                        let mut new_entry = NestedElement {
+                            kind,
+                            doc: Vec::new(),
+                            doc_span: None,
+                            text: String::new(),
+                            span: span.clone(),
+                        };
+
+                        if let Some((_, doc_span)) = &current_doc {
+                            if let Some(reason) =
+                                self.attachment_violation(rope, doc_span, span, comments)
+                            {
+                                unattach(unattached_docs, &mut current_doc, reason);
+                            }
+                        }
+                        if let Some((doc, doc_span)) = current_doc.take() {
+                            new_entry.doc = doc;
+                            new_entry.doc_span = Some(doc_span);
+                        }
+
+                        new_entry
+                    });
+                    current.span.end = span.end;
+
+                    if depth == 0 && matches!(token, Token::Semicolon | Token::CurlyClose) {
+                        entries.push(self.finish_nested_element(current));
+                    } else {
+                        entry = Some(current);
+                    }
+                }
+            }
+        }
+
+        if let Some(current) = entry {
+            entries.push(self.finish_nested_element(current));
+        }
+
+        let reason = UnattachedReason::EndOfBlock;
+        unattach(unattached_docs, &mut current_doc, reason);
+
+        entries
+    }
+
+    fn finish_nested_element(&self, mut nested: NestedElement) -> NestedElement {
+        nested.text = self.owned_slice(nested.span.clone());
+        nested
+    }
+
+    /// finds the block of consecutive plain comments directly above `element_span`, if any.
+    pub(crate) fn plain_comment_block(
+        &self,
+        rope: &Rope,
+        element_span: &Span,
+        comments: &[Span],
+    ) -> Option<Span> {
+        let preceding = comments.partition_point(|comment| comment.end <= element_span.start);
+
+        let mut block: Option<Span> = None;
+        let mut next_start = element_span.start;
+
+        for comment in comments[..preceding].iter().rev() {
+            if !directly_above(rope, comment, next_start) {
+                break;
+            }
+
+            let end = block.map_or(comment.end, |block| block.end);
+            block = Some(comment.start..end);
+            next_start = comment.start;
+        }
+
+        block
+    }
+
+    /// checks whether the attachment policy forbids attaching the documentation at `doc_span`
+    /// to the element at `element_span`, and if so, returns the reason.
+    fn attachment_violation(
+        &self,
+        rope: &Rope,
+        doc_span: &Span,
+        element_span: &Span,
+        comments: &[Span],
+    ) -> Option<UnattachedReason> {
+        let policy = &self.attachment_policy;
+
+        let gap = doc_span.end..element_span.start;
+        let comment_in_gap = comments
+            .iter()
+            .any(|comment| gap.start <= comment.start && comment.end <= gap.end);
+        if comment_in_gap && !policy.allow_plain_comments {
+            return Some(UnattachedReason::InterveningComment);
+        }
+
+        if let Some(max) = policy.max_blank_lines {
+            let found = blank_lines_between(rope, doc_span, element_span);
+            if found > max {
+                return Some(UnattachedReason::TooManyBlankLines { found, max });
+            }
+        }
+
+        None
+    }
+
+    fn documentation(&self, style: Style, span: Span) -> Vec<DocumentationTag> {
+        let input = self.slice(span.clone());
+        let mut body = ContentLines::new(input, span.clone(), Builder::chars_to_trim(style));
+        if let Style::Trailing = style {
+            body = body.with_prefix(TRAILING_DOC_PREFIX);
+        }
+
+        DocumentationTag::from_spanned_iter(body, span)
     }
 
     fn process_intermediate(
@@ -221,12 +736,11 @@ impl<'src> Builder<'src> {
                 let ast = Ast::FreeFormComment { text };
                 DocOrAst::Ast(ast)
             }
+            Intermediate::Documentation(Style::Trailing, span) => {
+                DocOrAst::TrailingDoc(self.documentation(Style::Trailing, span))
+            }
             Intermediate::Documentation(style, span) => {
-                let input = self.slice(span.clone());
-                let body = ContentLines::new(input, span.clone(), Builder::chars_to_trim(style));
-
-                let doc = DocumentationTag::from_spanned_iter(body, span);
-                DocOrAst::Doc(doc)
+                DocOrAst::Doc(self.documentation(style, span))
             }
             Intermediate::Methods(block) => {
                 let block = self.trimmed_block_slice(block).to_string();
@@ -437,6 +951,7 @@ pub struct ContentLines<'src, 'trim> {
     input: &'src str,
     span: Span,
     chars_to_trim: &'trim [char],
+    prefix: Option<&'trim str>,
 }
 
 impl<'src, 'trim> ContentLines<'src, 'trim> {
@@ -449,9 +964,16 @@ impl<'src, 'trim> ContentLines<'src, 'trim> {
             input,
             span,
             chars_to_trim,
+            prefix: None,
         }
     }
 
+    /// strips `prefix` once from the start of each line, before trimming
+    pub fn with_prefix(mut self, prefix: &'trim str) -> ContentLines<'src, 'trim> {
+        self.prefix = Some(prefix);
+        self
+    }
+
     fn next_split(&self) -> usize {
         self.input
             .find('\n')
@@ -490,6 +1012,11 @@ impl<'a, 'b> Iterator for ContentLines<'a, 'b> {
 
         let mut terminated = TerminatedStr::from(line);
 
+        if let Some(prefix) = self.prefix {
+            let content = terminated.content.trim_start();
+            terminated.content = content.strip_prefix(prefix).unwrap_or(content);
+        }
+
         let should_trim = |ch| self.chars_to_trim.contains(&ch) || ch.is_ascii_whitespace();
         terminated.content = terminated.content.trim_matches(should_trim);
 
@@ -6,11 +6,12 @@ use chumsky::prelude::*;
 use itertools::Itertools;
 use std::iter;
 
-pub const SYNC_TOKENS: [Token; 10] = [
+pub const SYNC_TOKENS: [Token; 11] = [
     Token::FreeFormSlashed,
     Token::FreeFormStarred,
     Token::CvlDocSlashed,
     Token::CvlDocStarred,
+    Token::CvlDocTrailing,
     Token::Ghost,
     Token::Definition,
     Token::Rule,
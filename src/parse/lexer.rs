@@ -4,8 +4,11 @@ use crate::util::Span;
 use chumsky::prelude::*;
 
 pub fn cvl_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Simple<char>> {
+    let cvldoc_trailing = just("///<")
+        .then(take_until(newline_or_end()))
+        .to(Token::CvlDocTrailing);
     let cvldoc_slashed_line = just("///")
-        .then_ignore(none_of('/').rewind())
+        .then_ignore(none_of("/<").rewind())
         .then(take_until(newline_or_end()));
     let cvldoc_slashed = cvldoc_slashed_line
         .repeated()
@@ -144,6 +147,7 @@ pub fn cvl_lexer() -> impl Parser<char, Vec<(Token, Span)>, Error = Simple<char>
     };
 
     choice((
+        cvldoc_trailing,
         cvldoc_slashed,
         cvldoc_starred,
         freeform_slashed,
@@ -1,14 +1,17 @@
 mod cvl2;
 
-use super::builder::Builder;
+use super::builder::{AttachmentPolicy, Builder};
 use super::Token;
+use crate::util::{ByteSpan, RangeConverter, Span};
 use crate::CvlElement;
-use crate::{Ast, Param, TagKind};
+use crate::{Ast, NestedKind, Param, TagKind, UnattachedReason};
 use assert_matches::assert_matches;
 use color_eyre::eyre::{bail, Context};
 use color_eyre::Report;
 use indoc::indoc;
 use itertools::Itertools;
+use lsp_types::DiagnosticSeverity;
+use ropey::Rope;
 use std::iter::Iterator;
 
 fn parse_exactly_one(src: &str) -> Result<CvlElement, Report> {
@@ -605,3 +608,482 @@ fn repeated_iterations_caused_by_improper_recovery() {
 
     let _ = Builder::new(src).build();
 }
+
+#[test]
+fn unattached_documentation_is_reported() {
+    let src = indoc! {"
+        /// @notice this one is shadowed
+        /** @notice by this one */
+        rule foo { }
+
+        /// @notice this one is followed by a freeform comment
+        //// # Section
+
+        rule bar { }
+
+        /// @notice this one is at the end of the file
+    "};
+
+    let parse_result = Builder::new(src).build_with_diagnostics().unwrap();
+    assert_eq!(parse_result.elements.len(), 3);
+
+    let foo = &parse_result.elements[0];
+    assert_eq!(foo.doc[0].description, "by this one");
+    assert!(parse_result.elements[2].doc.is_empty());
+
+    let reasons = parse_result
+        .unattached_docs
+        .iter()
+        .map(|unattached| unattached.reason)
+        .collect_vec();
+    assert_eq!(
+        reasons,
+        [
+            UnattachedReason::FollowedByDocumentation,
+            UnattachedReason::FollowedByFreeForm,
+            UnattachedReason::EndOfFile
+        ]
+    );
+
+    let spans = parse_result
+        .unattached_docs
+        .iter()
+        .map(|unattached| Span::byte_slice(&unattached.span, src).unwrap());
+    let expected = [
+        "/// @notice this one is shadowed\n",
+        "/// @notice this one is followed by a freeform comment\n",
+        "/// @notice this one is at the end of the file\n",
+    ];
+    assert!(spans.eq(expected));
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostics = parse_result.enumerate_diagnostics(converter);
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].range.start.line, 0);
+    assert_eq!(diagnostics[1].range.start.line, 4);
+    assert_eq!(diagnostics[2].range.start.line, 9);
+}
+
+#[test]
+fn attachment_policy() {
+    let src = indoc! {"
+        /// @notice separated by a blank line
+
+        rule blank { }
+
+        /// @notice separated by a comment
+        // some comment
+        rule comment { }
+
+        /// @notice separated by a parse error
+        $%^ garbage
+        rule garbage { }
+
+        ghost mathint sumBalances; ///< running total of balances
+    "};
+
+    let permissive = Builder::new(src).build_with_diagnostics().unwrap();
+    assert!(permissive.unattached_docs.is_empty());
+    assert!(permissive
+        .elements
+        .iter()
+        .all(|element| element.doc.len() == 1));
+
+    let strict = Builder::new(src)
+        .with_attachment_policy(AttachmentPolicy::strict(0))
+        .build_with_diagnostics()
+        .unwrap();
+    let reasons = strict
+        .unattached_docs
+        .iter()
+        .map(|unattached| unattached.reason)
+        .collect_vec();
+    assert_eq!(
+        reasons,
+        [
+            UnattachedReason::TooManyBlankLines { found: 1, max: 0 },
+            UnattachedReason::InterveningComment,
+            UnattachedReason::InterveningParseError,
+        ]
+    );
+
+    let [blank, comment, garbage, ghost] = strict.elements.as_slice() else {
+        panic!("expected exactly 4 elements")
+    };
+    assert!(blank.doc.is_empty() && comment.doc.is_empty() && garbage.doc.is_empty());
+    assert_eq!(ghost.ast.name(), Some("sumBalances"));
+    assert_eq!(ghost.doc[0].description, "running total of balances");
+
+    let one_blank_line_allowed = AttachmentPolicy {
+        max_blank_lines: Some(1),
+        ..AttachmentPolicy::default()
+    };
+    let parse_result = Builder::new(src)
+        .with_attachment_policy(one_blank_line_allowed)
+        .build_with_diagnostics()
+        .unwrap();
+    assert!(!parse_result.elements[0].doc.is_empty());
+}
+
+#[test]
+fn trailing_doc_without_previous_element() {
+    let src = indoc! {"
+        ///< nothing to attach to
+        rule foo { }
+    "};
+
+    let parse_result = Builder::new(src)
+        .with_attachment_policy(AttachmentPolicy::strict(0))
+        .build_with_diagnostics()
+        .unwrap();
+
+    assert!(parse_result.elements[0].doc.is_empty());
+    assert_matches!(
+        parse_result.unattached_docs.as_slice(),
+        [unattached] if unattached.reason == UnattachedReason::NoPreviousElement
+    );
+}
+
+#[test]
+fn trailing_doc_is_not_absorbed_into_preceding_doc_block() {
+    let src = indoc! {"
+        /// @notice leading documentation
+        /// @dev more of it
+        ///< trailing documentation
+    "};
+
+    let tokens = Builder::new(src)
+        .lex()
+        .unwrap()
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect_vec();
+
+    assert_eq!(tokens, [Token::CvlDocSlashed, Token::CvlDocTrailing]);
+}
+
+#[test]
+fn trailing_documentation() {
+    let src = indoc! {"
+        /// @notice leading documentation
+        ghost mathint sumBalances; ///< running total of balances
+
+        methods {
+            function balanceOf(address) external returns (uint256) envfree; ///< @dev balance
+            function totalSupply() external returns (uint256) envfree;
+            ///< not on the same line as any entry
+            function _.transfer(address, uint256) external => DISPATCHER(true); ///< dispatched
+        }
+
+        rule foo { }
+        ///< not on the same line as any element
+    "};
+
+    let parse_result = Builder::new(src).build_with_diagnostics().unwrap();
+    let [ghost, methods, foo] = parse_result.elements.as_slice() else {
+        panic!("expected exactly 3 elements")
+    };
+
+    let [leading, trailing] = ghost.doc.as_slice() else {
+        panic!("expected both leading and trailing documentation")
+    };
+    assert_eq!(leading.description, "leading documentation");
+    assert_eq!(trailing.kind, TagKind::Notice);
+    assert_eq!(trailing.description, "running total of balances");
+    let doc_span = ghost.doc_span.clone().unwrap();
+    assert_eq!(
+        doc_span.byte_slice(src).unwrap().trim_end(),
+        "/// @notice leading documentation\nghost mathint sumBalances; ///< running total of balances"
+    );
+    assert_eq!(ghost.span(), doc_span);
+    assert!(methods.doc.is_empty());
+    assert!(foo.doc.is_empty());
+
+    let [balance_of, total_supply, transfer] = methods.nested.as_slice() else {
+        panic!("expected exactly 3 methods entries")
+    };
+    assert_eq!(
+        balance_of.text,
+        "function balanceOf(address) external returns (uint256) envfree;"
+    );
+    assert_eq!(balance_of.doc[0].kind, TagKind::Dev);
+    assert_eq!(balance_of.doc[0].description, "balance");
+    let doc_span = balance_of.doc_span.clone().unwrap();
+    assert_eq!(
+        doc_span.byte_slice(src).map(str::trim_end),
+        Some("///< @dev balance")
+    );
+    assert!(total_supply.doc.is_empty());
+    assert_eq!(transfer.doc[0].description, "dispatched");
+
+    assert_eq!(parse_result.unattached_docs.len(), 2);
+    assert!(parse_result
+        .unattached_docs
+        .iter()
+        .all(|unattached| unattached.reason == UnattachedReason::NoPreviousElement));
+}
+
+#[test]
+fn documentation_inside_blocks() {
+    let src = indoc! {"
+        methods {
+            /// @notice the balance of an account
+            function balanceOf(address) external returns (uint256) envfree;
+
+            /// @param a not supported here
+            function totalSupply() external returns (uint256) envfree;
+        }
+
+        invariant solvency()
+            totalSupply() <= balance()
+            {
+                /// @dev transfers are checked separately
+                preserved transfer(address to, uint256 amount) with (env e) {
+                    require e.msg.sender != to;
+                }
+
+                preserved {
+                    require true;
+                }
+                /// @notice nothing follows this
+            }
+
+        ghost mapping(address => uint256) balances {
+            /// starts at zero
+            init_state axiom forall address a. balances[a] == 0;
+        }
+    "};
+
+    let parse_result = Builder::new(src).build_with_diagnostics().unwrap();
+    let [methods, invariant, ghost] = parse_result.elements.as_slice() else {
+        panic!("expected exactly 3 elements")
+    };
+
+    let [balance_of, total_supply] = methods.nested.as_slice() else {
+        panic!("expected exactly 2 methods entries")
+    };
+    assert_eq!(balance_of.kind, NestedKind::MethodsEntry);
+    assert_eq!(balance_of.doc[0].description, "the balance of an account");
+    assert!(balance_of.doc_span.is_some());
+    assert_eq!(total_supply.doc[0].kind, TagKind::Param);
+
+    let [transfer, preserved] = invariant.nested.as_slice() else {
+        panic!("expected exactly 2 preserved blocks")
+    };
+    assert_eq!(transfer.kind, NestedKind::Preserved);
+    assert!(transfer
+        .text
+        .starts_with("preserved transfer(address to, uint256 amount)"));
+    assert!(transfer.text.ends_with('}'));
+    assert_eq!(transfer.doc[0].kind, TagKind::Dev);
+    assert!(preserved.doc.is_empty());
+
+    let axiom = ghost.nested.iter().exactly_one().unwrap();
+    assert_eq!(axiom.kind, NestedKind::Axiom);
+    assert_eq!(axiom.doc[0].description, "starts at zero");
+
+    let unattached = parse_result.unattached_docs.iter().exactly_one().unwrap();
+    assert_eq!(unattached.reason, UnattachedReason::EndOfBlock);
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostics = methods.enumerate_diagnostics(converter);
+    let diagnostic = diagnostics.iter().exactly_one().unwrap();
+    assert_eq!(
+        diagnostic.message,
+        "this tag is unsupported for methods entries"
+    );
+    assert_eq!(diagnostic.range.start.line, 4);
+}
+
+#[test]
+fn documentation_inside_an_entry_is_not_attached() {
+    let src = indoc! {"
+        invariant inv(uint x) x > 0 {
+            preserved {
+                /// inner doc
+                require x > 1;
+            }
+            preserved foo() with (env e) {
+                require true;
+            }
+        }
+    "};
+
+    let parse_result = Builder::new(src).build_with_diagnostics().unwrap();
+    let invariant = parse_result.elements.iter().exactly_one().unwrap();
+    let [preserved, foo] = invariant.nested.as_slice() else {
+        panic!("expected exactly 2 preserved blocks")
+    };
+    assert!(preserved.doc.is_empty());
+    assert!(foo.doc.is_empty());
+
+    let unattached = parse_result.unattached_docs.iter().exactly_one().unwrap();
+    assert_eq!(unattached.reason, UnattachedReason::InsideEntry);
+    assert_eq!(
+        unattached.span.byte_slice(src).map(str::trim_end),
+        Some("/// inner doc")
+    );
+}
+
+#[test]
+fn spec_file_documentation() {
+    let before_import = indoc! {r#"
+        /// @title ERC20 spec
+        /// @notice covers the basic token properties
+        import "erc20_methods.spec";
+        using DummyERC20 as token;
+        using Vault as vault;
+
+        rule foo { }
+    "#};
+
+    let spec_file = Builder::new(before_import).build_spec_file().unwrap();
+    assert_eq!(spec_file.title(), Some("ERC20 spec"));
+    assert_eq!(spec_file.doc.len(), 2);
+    assert!(spec_file
+        .elements
+        .iter()
+        .all(|element| element.doc.is_empty()));
+
+    let import = spec_file.imports.iter().exactly_one().unwrap();
+    assert_eq!(import.imported, "erc20_methods.spec");
+    assert_eq!(
+        import.span.byte_slice(before_import),
+        Some(r#"import "erc20_methods.spec";"#)
+    );
+    let aliases = spec_file
+        .usings
+        .iter()
+        .map(|using| (using.contract_name.as_str(), using.spec_name.as_str()))
+        .collect_vec();
+    assert_eq!(aliases, [("DummyERC20", "token"), ("Vault", "vault")]);
+
+    let title_then_blank_line = indoc! {"
+        /// @title Vault spec
+
+        /// @title documents the rule
+        rule foo { }
+    "};
+    let spec_file = Builder::new(title_then_blank_line)
+        .with_attachment_policy(AttachmentPolicy::strict(0))
+        .build_spec_file()
+        .unwrap();
+    assert_eq!(spec_file.title(), Some("Vault spec"));
+    assert!(spec_file.unattached_docs.is_empty());
+    assert_eq!(
+        spec_file.elements[0].doc[0].description,
+        "documents the rule"
+    );
+
+    let title_then_rule = indoc! {"
+        /// @title documents the file, not the rule
+
+        rule foo { }
+    "};
+    let spec_file = Builder::new(title_then_rule).build_spec_file().unwrap();
+    assert_eq!(spec_file.title(), Some("documents the file, not the rule"));
+    assert!(spec_file.elements[0].doc.is_empty());
+
+    let adjacent_title = indoc! {"
+        /// @title documents the rule
+        rule foo { }
+    "};
+    let spec_file = Builder::new(adjacent_title).build_spec_file().unwrap();
+    assert!(spec_file.doc.is_empty());
+    assert_eq!(spec_file.elements[0].doc.len(), 1);
+}
+
+#[test]
+fn spec_file_documentation_before_freeform() {
+    let freeform_before_import = indoc! {r#"
+        /// @notice covers the basic token properties
+        //// ## Setup
+        import "erc20_methods.spec";
+
+        rule foo { }
+    "#};
+    let spec_file = Builder::new(freeform_before_import)
+        .build_spec_file()
+        .unwrap();
+    let notice = spec_file.doc.iter().exactly_one().unwrap();
+    assert_eq!(notice.description, "covers the basic token properties");
+    assert!(spec_file.unattached_docs.is_empty());
+
+    let title_then_blank_line = indoc! {"
+        /** not about the file */
+        /// @title Vault spec
+
+        //// ## Rules
+        rule foo { }
+    "};
+    let spec_file = Builder::new(title_then_blank_line)
+        .build_spec_file()
+        .unwrap();
+    assert_eq!(spec_file.title(), Some("Vault spec"));
+    let skipped = spec_file.unattached_docs.iter().exactly_one().unwrap();
+    assert_eq!(skipped.reason, UnattachedReason::FollowedByDocumentation);
+
+    let title_then_freeform = indoc! {"
+        /// @title not followed by a blank line
+        //// ## Rules
+
+        rule foo { }
+    "};
+    let spec_file = Builder::new(title_then_freeform).build_spec_file().unwrap();
+    assert!(spec_file.doc.is_empty());
+    let unattached = spec_file.unattached_docs.iter().exactly_one().unwrap();
+    assert_eq!(unattached.reason, UnattachedReason::FollowedByFreeForm);
+}
+
+#[test]
+fn parse_errors_are_reported() {
+    let src = indoc! {"
+        /// @title Vault spec
+        /// @param x not a file tag
+
+        rule foo { }
+
+        $%^ garbage
+
+        rule bar { }
+    "};
+    let converter = RangeConverter::new(Rope::from_str(src));
+
+    let parse_result = Builder::new(src).build_with_diagnostics().unwrap();
+    let diagnostics = parse_result.enumerate_diagnostics(converter.clone());
+    // without the file documentation, the first block documents `foo`
+    let parse_error = diagnostics.last().unwrap();
+    assert_eq!(parse_error.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(parse_error.message, "this code could not be parsed");
+    assert_eq!(parse_error.range.start.line, 5);
+
+    // the diagnostics of the file's own documentation come first
+    let spec_file = Builder::new(src).build_spec_file().unwrap();
+    let diagnostics = spec_file.enumerate_diagnostics(converter);
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect_vec();
+    assert_eq!(
+        messages,
+        [
+            "this tag is unsupported for file documentation",
+            "this code could not be parsed"
+        ]
+    );
+}
+
+#[test]
+fn trailing_doc_prefix_is_stripped_once() {
+    let src = indoc! {"
+        ghost mathint total; ///< <= max supply
+        ghost mathint count; ///<<< nested </
+    "};
+
+    let elements = Builder::new(src).build().unwrap();
+    let descriptions = elements
+        .iter()
+        .map(|element| element.doc[0].description.as_str())
+        .collect_vec();
+    assert_eq!(descriptions, ["<= max supply", "<< nested </"]);
+}
@@ -1,5 +1,5 @@
 use crate::util::Span;
-use crate::Param;
+use crate::{NestedKind, Param};
 use itertools::Itertools;
 use std::fmt::{Display, Formatter};
 
@@ -16,6 +16,7 @@ pub enum Token {
     Filtered,
     CvlDocSlashed,
     CvlDocStarred,
+    CvlDocTrailing,
     FreeFormSlashed,
     FreeFormStarred,
     RoundOpen,
@@ -121,6 +122,7 @@ impl Display for Token {
 
             Token::CvlDocSlashed
             | Token::CvlDocStarred
+            | Token::CvlDocTrailing
             | Token::FreeFormSlashed
             | Token::FreeFormStarred
             | Token::SingleLineComment
@@ -218,8 +220,34 @@ pub enum Intermediate {
     ParseError,
 }
 
-#[derive(Debug, Clone)]
+impl Intermediate {
+    /// the block whose entries should be parsed as nested elements, if any
+    pub(super) fn nested_block(&self) -> Option<(NestedKind, Span)> {
+        match self {
+            Intermediate::Methods(block) => Some((NestedKind::MethodsEntry, block.clone())),
+            Intermediate::Invariant {
+                proof: Some(proof), ..
+            }
+            | Intermediate::UseInvariant {
+                proof: Some(proof), ..
+            } => Some((NestedKind::Preserved, proof.clone())),
+            Intermediate::GhostMapping {
+                axioms: Some(axioms),
+                ..
+            }
+            | Intermediate::GhostFunction {
+                axioms: Some(axioms),
+                ..
+            } => Some((NestedKind::Axiom, axioms.clone())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum Style {
     Slashed,
     Starred,
+    /// `///<` documentation, which documents the element that precedes it.
+    Trailing,
 }
@@ -0,0 +1,405 @@
+//! a set of spec files, connected by their `import` statements.
+
+pub mod fs;
+
+use crate::parse::builder::Builder;
+use crate::util::{RangeConverter, Span};
+use crate::{CvlElement, SpecFile};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use fs::{DiskFileSystem, FileSystem};
+use itertools::Itertools;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use ropey::Rope;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests;
+
+/// a parsed file of a [SpecProject]
+#[derive(Debug, Clone)]
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub src: String,
+    pub spec: SpecFile,
+    /// for each of `spec.imports`, the index of the imported file in [SpecProject::files],
+    /// or `None` if it could not be resolved
+    pub resolved_imports: Vec<Option<usize>>,
+}
+
+impl ProjectFile {
+    pub fn converter(&self) -> RangeConverter {
+        RangeConverter::new(Rope::from_str(&self.src))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportProblemKind {
+    /// no file was found at any of the tried paths
+    Missing { tried: Vec<PathBuf> },
+    /// the file was found, but could not be read
+    Unreadable { path: PathBuf, error: String },
+    /// the import leads back to the importing file. the cycle starts and ends with the imported file.
+    Cycle { cycle: Vec<PathBuf> },
+}
+
+/// a problem with an `import` statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProblem {
+    /// the index of the importing file in [SpecProject::files]
+    pub file: usize,
+    pub imported: String,
+    /// the span of the `import` statement
+    pub span: Span,
+    pub kind: ImportProblemKind,
+}
+
+impl ImportProblem {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let message = match &self.kind {
+            ImportProblemKind::Missing { tried } => format!(
+                "cannot find imported file {}. tried: {}",
+                self.imported,
+                tried.iter().map(|path| path.display()).join(", ")
+            ),
+            ImportProblemKind::Unreadable { path, error } => {
+                format!("cannot read imported file {}: {error}", path.display())
+            }
+            ImportProblemKind::Cycle { cycle } => format!(
+                "import cycle: {}",
+                cycle.iter().map(|path| path.display()).join(" -> ")
+            ),
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// resolves `.` and `..` components without touching the file system,
+/// so that different spellings of the same path are recognized as one file
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// replaces a prefix of an imported path, like the remappings of `solc`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remapping {
+    pub prefix: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for Remapping {
+    type Err = color_eyre::Report;
+
+    /// parses a remapping written as `prefix=path`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((prefix, path)) if !prefix.is_empty() => Ok(Remapping {
+                prefix: prefix.to_string(),
+                path: PathBuf::from(path),
+            }),
+            _ => bail!("remapping must be written as prefix=path: {s}"),
+        }
+    }
+}
+
+impl Remapping {
+    /// the rest of `imported` after the prefix, if the prefix matches whole path components
+    fn strip_prefix<'a>(&self, imported: &'a str) -> Option<&'a str> {
+        let rest = imported.strip_prefix(&self.prefix)?;
+        let at_boundary = self.prefix.ends_with('/') || rest.is_empty() || rest.starts_with('/');
+        at_boundary.then(|| rest.trim_start_matches('/'))
+    }
+}
+
+/// loads a [SpecProject]
+#[derive(Debug, Clone)]
+pub struct ProjectBuilder {
+    roots: Vec<PathBuf>,
+    search_roots: Vec<PathBuf>,
+    remappings: Vec<Remapping>,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl ProjectBuilder {
+    /// `roots` are the files the project starts from. any file they import,
+    /// directly or indirectly, is also part of the project.
+    pub fn new(roots: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        ProjectBuilder {
+            roots: roots.into_iter().map(Into::into).collect(),
+            search_roots: Vec::new(),
+            remappings: Vec::new(),
+            file_system: Arc::new(DiskFileSystem),
+        }
+    }
+
+    /// imports starting with the prefix of a remapping, followed by `/` or nothing, are resolved
+    /// to its path instead. when several prefixes match, the longest is used.
+    pub fn with_remappings(mut self, remappings: impl IntoIterator<Item = Remapping>) -> Self {
+        self.remappings = remappings.into_iter().collect();
+        self
+    }
+
+    /// the file system that every file is read from. the default is the disk.
+    pub fn with_file_system(mut self, file_system: impl FileSystem + 'static) -> Self {
+        self.file_system = Arc::new(file_system);
+        self
+    }
+
+    /// directories in which imports are looked up, in order,
+    /// when they are not found relative to the importing file
+    pub fn with_search_roots(
+        mut self,
+        search_roots: impl IntoIterator<Item = impl Into<PathBuf>>,
+    ) -> Self {
+        self.search_roots = search_roots.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// fails only if one of the roots cannot be read.
+    /// problems with imports are reported in [SpecProject::problems].
+    pub fn build(self) -> Result<SpecProject> {
+        let mut loader = Loader {
+            search_roots: &self.search_roots,
+            remappings: &self.remappings,
+            file_system: self.file_system.as_ref(),
+            project: SpecProject::default(),
+            indices: HashMap::new(),
+            visited: HashSet::new(),
+        };
+
+        for root in &self.roots {
+            let index = match loader.file_index(root) {
+                Ok(index) => index,
+                Err(error) => bail!("cannot read {}: {error}", root.display()),
+            };
+            if !loader.project.roots.contains(&index) {
+                loader.project.roots.push(index);
+            }
+        }
+
+        for root in loader.project.roots.clone() {
+            if !loader.visited.contains(&root) {
+                loader.visit(root, &mut Vec::new());
+            }
+        }
+
+        Ok(loader.project)
+    }
+}
+
+/// the files reachable from a set of root files through their imports. each file is parsed once.
+#[derive(Debug, Clone, Default)]
+pub struct SpecProject {
+    /// every file of the project, in the order they were discovered
+    pub files: Vec<ProjectFile>,
+    /// the indices in `files` of the root files
+    pub roots: Vec<usize>,
+    pub problems: Vec<ImportProblem>,
+}
+
+impl SpecProject {
+    pub fn file(&self, path: &Path) -> Option<&ProjectFile> {
+        let path = normalize_path(path);
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// the indices of the files imported by `file`, directly or indirectly, not including `file` itself
+    pub fn transitive_imports(&self, file: usize) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut pending = vec![file];
+
+        while let Some(current) = pending.pop() {
+            for &imported in self.files[current].resolved_imports.iter().flatten() {
+                if imported != file && !found.contains(&imported) {
+                    found.push(imported);
+                    pending.push(imported);
+                }
+            }
+        }
+
+        found
+    }
+
+    /// the elements of every file imported by `file`, directly or indirectly,
+    /// in the form accepted by cross-file functions such as [crate::inherit::inherit_docs]
+    pub fn imported_elements(&self, file: usize) -> Vec<&[CvlElement]> {
+        self.transitive_imports(file)
+            .into_iter()
+            .map(|index| self.files[index].spec.elements.as_slice())
+            .collect()
+    }
+
+    /// the diagnostics of the import problems, with the path of the file each belongs to
+    pub fn import_diagnostics(&self) -> Vec<(&Path, Diagnostic)> {
+        let converters: HashMap<usize, RangeConverter> = self
+            .problems
+            .iter()
+            .map(|problem| problem.file)
+            .unique()
+            .map(|file| (file, self.files[file].converter()))
+            .collect();
+
+        self.problems
+            .iter()
+            .map(|problem| {
+                let file = &self.files[problem.file];
+                (
+                    file.path.as_path(),
+                    problem.to_diagnostic(&converters[&problem.file]),
+                )
+            })
+            .collect()
+    }
+}
+
+struct Loader<'a> {
+    search_roots: &'a [PathBuf],
+    remappings: &'a [Remapping],
+    file_system: &'a dyn FileSystem,
+    project: SpecProject,
+    /// maps each normalized path to its index in the project
+    indices: HashMap<PathBuf, usize>,
+    visited: HashSet<usize>,
+}
+
+impl Loader<'_> {
+    /// the index of the file at `path`, which is read and parsed if it was not loaded yet.
+    fn file_index(&mut self, path: &Path) -> Result<usize> {
+        let path = normalize_path(path);
+        if let Some(&index) = self.indices.get(&path) {
+            return Ok(index);
+        }
+
+        let src = self
+            .file_system
+            .read_to_string(&path)
+            .map_err(|error| eyre!(error))?;
+        let spec = Builder::new(&src).build_spec_file()?;
+        let resolved_imports = vec![None; spec.imports.len()];
+
+        let index = self.project.files.len();
+        self.project.files.push(ProjectFile {
+            path: path.clone(),
+            src,
+            spec,
+            resolved_imports,
+        });
+        self.indices.insert(path, index);
+
+        Ok(index)
+    }
+
+    /// the paths at which an import may be found, in order of preference
+    fn candidates(&self, importing: &Path, imported: &str) -> Vec<PathBuf> {
+        let remapping = self
+            .remappings
+            .iter()
+            .filter_map(|remapping| Some((remapping, remapping.strip_prefix(imported)?)))
+            .max_by_key(|(remapping, _)| remapping.prefix.len());
+
+        // a remapped import is looked up at its remapped path, rather than next to the importing file
+        let (imported, first) = match remapping {
+            Some((remapping, rest)) => {
+                let remapped = remapping.path.join(rest);
+                (remapped.clone(), remapped)
+            }
+            None => {
+                let relative = importing.parent().unwrap_or(Path::new("")).join(imported);
+                (PathBuf::from(imported), relative)
+            }
+        };
+        if imported.is_absolute() {
+            return vec![normalize_path(&imported)];
+        }
+
+        let in_search_roots = self.search_roots.iter().map(|root| root.join(&imported));
+
+        std::iter::once(first)
+            .chain(in_search_roots)
+            .map(|path| normalize_path(&path))
+            .unique()
+            .collect()
+    }
+
+    fn visit(&mut self, index: usize, stack: &mut Vec<usize>) {
+        stack.push(index);
+        self.visited.insert(index);
+
+        let file = &self.project.files[index];
+        let imports = file.spec.imports.clone();
+        let importing = file.path.clone();
+
+        for (i, import) in imports.into_iter().enumerate() {
+            let candidates = self.candidates(&importing, &import.imported);
+            let problem = |kind| ImportProblem {
+                file: index,
+                imported: import.imported.clone(),
+                span: import.span.clone(),
+                kind,
+            };
+
+            let Some(found) = candidates
+                .iter()
+                .find(|path| self.file_system.is_file(path))
+            else {
+                let kind = ImportProblemKind::Missing { tried: candidates };
+                self.project.problems.push(problem(kind));
+                continue;
+            };
+
+            let target = match self.file_index(found) {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    let kind = ImportProblemKind::Unreadable {
+                        path: found.clone(),
+                        error: error.to_string(),
+                    };
+                    self.project.problems.push(problem(kind));
+                    continue;
+                }
+            };
+            self.project.files[index].resolved_imports[i] = Some(target);
+
+            if let Some(position) = stack.iter().position(|&on_stack| on_stack == target) {
+                let cycle = stack[position..]
+                    .iter()
+                    .chain([&target])
+                    .map(|&file| self.project.files[file].path.clone())
+                    .collect();
+                self.project
+                    .problems
+                    .push(problem(ImportProblemKind::Cycle { cycle }));
+            } else if !self.visited.contains(&target) {
+                self.visit(target, stack);
+            }
+        }
+
+        stack.pop();
+    }
+}
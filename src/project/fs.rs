@@ -0,0 +1,80 @@
+//! the file system a [super::SpecProject] is loaded from.
+
+use super::normalize_path;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub trait FileSystem: Debug + Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn is_file(&self, path: &Path) -> bool;
+}
+
+/// reads files from the disk
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+}
+
+/// files held in memory, such as the unsaved buffers of an editor.
+/// files that are not in memory may be read from a fallback file system.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryFileSystem {
+    files: HashMap<PathBuf, String>,
+    fallback: Option<Arc<dyn FileSystem>>,
+}
+
+impl InMemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// files in memory take precedence over the files of `fallback`
+    pub fn with_fallback(fallback: impl FileSystem + 'static) -> Self {
+        InMemoryFileSystem {
+            files: HashMap::new(),
+            fallback: Some(Arc::new(fallback)),
+        }
+    }
+
+    /// adds the file, or replaces its contents
+    pub fn insert(&mut self, path: impl AsRef<Path>, contents: impl Into<String>) {
+        self.files
+            .insert(normalize_path(path.as_ref()), contents.into());
+    }
+
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<String> {
+        self.files.remove(&normalize_path(path.as_ref()))
+    }
+}
+
+impl FileSystem for InMemoryFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match (self.files.get(&normalize_path(path)), &self.fallback) {
+            (Some(contents), _) => Ok(contents.clone()),
+            (None, Some(fallback)) => fallback.read_to_string(path),
+            (None, None) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in memory", path.display()),
+            )),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize_path(path))
+            || self
+                .fallback
+                .as_ref()
+                .is_some_and(|fallback| fallback.is_file(path))
+    }
+}
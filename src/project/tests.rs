@@ -0,0 +1,253 @@
+use super::fs::{DiskFileSystem, InMemoryFileSystem};
+use super::{ImportProblemKind, ProjectBuilder, Remapping};
+use crate::unused::UnusedConfig;
+use crate::util::ByteSpan;
+use assert_matches::assert_matches;
+use indoc::indoc;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/project")
+}
+
+#[test]
+fn imports_are_resolved_once() {
+    let dir = fixtures();
+    let project = ProjectBuilder::new([dir.join("main.spec")])
+        .with_search_roots([dir.join("common")])
+        .build()
+        .unwrap();
+
+    let paths = project
+        .files
+        .iter()
+        .map(|file| file.path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "main.spec",
+            "lib/erc20.spec",
+            "common/shared.spec",
+            "cycle/a.spec",
+            "cycle/b.spec"
+        ]
+        .map(PathBuf::from)
+    );
+    assert_eq!(project.roots, [0]);
+    assert_eq!(
+        project.files[0].resolved_imports,
+        [Some(1), Some(2), None, Some(3)]
+    );
+    assert_eq!(project.files[1].resolved_imports, [Some(2)]);
+
+    let imported = project.imported_elements(1);
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0][0].ast.name(), Some("MAX"));
+    assert_eq!(project.transitive_imports(0).len(), 4);
+
+    let file = project.file(&dir.join("lib/../lib/erc20.spec")).unwrap();
+    assert_eq!(
+        file.spec.elements[1].title().as_deref(),
+        Some("transfers preserve the total supply")
+    );
+}
+
+#[test]
+fn missing_files_and_cycles_are_reported_on_the_import() {
+    let dir = fixtures();
+    let project = ProjectBuilder::new([dir.join("main.spec")])
+        .with_search_roots([dir.join("common")])
+        .build()
+        .unwrap();
+
+    assert_eq!(project.problems.len(), 2);
+
+    let missing = &project.problems[0];
+    assert_eq!(missing.file, 0);
+    assert_eq!(
+        missing.span.byte_slice(&project.files[0].src),
+        Some("import \"missing.spec\";")
+    );
+    assert_matches!(&missing.kind, ImportProblemKind::Missing { tried } if tried.len() == 2);
+
+    let cycle = &project.problems[1];
+    assert_eq!(project.files[cycle.file].path, dir.join("cycle/b.spec"));
+    let ImportProblemKind::Cycle { cycle: paths } = &cycle.kind else {
+        panic!("expected a cycle");
+    };
+    assert_eq!(
+        paths,
+        &["cycle/a.spec", "cycle/b.spec", "cycle/a.spec"].map(|path| dir.join(path))
+    );
+
+    let diagnostics = project.import_diagnostics();
+    assert_eq!(diagnostics[0].0, dir.join("main.spec"));
+    assert_eq!(diagnostics[0].1.range.start.line, 2);
+    assert!(diagnostics[1].1.message.starts_with("import cycle: "));
+}
+
+#[test]
+fn missing_root_is_an_error() {
+    let result = ProjectBuilder::new([fixtures().join("nonexistent.spec")]).build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn remappings_with_in_memory_files() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/repo/certora/main.spec",
+        "import \"specs-common/erc20.spec\";\nimport \"specs-common-extra/a.spec\";\n",
+    );
+    file_system.insert("/repo/common/specs/erc20.spec", "rule transfer { }");
+    file_system.insert("/repo/extra/a.spec", "rule a { }");
+
+    let remappings = [
+        "specs-common=/repo/common/specs",
+        "specs-common-extra=/repo/extra",
+    ]
+    .map(|remapping| remapping.parse::<Remapping>().unwrap());
+    let project = ProjectBuilder::new(["/repo/certora/main.spec"])
+        .with_remappings(remappings)
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+
+    assert!(project.problems.is_empty());
+    let paths = project
+        .files
+        .iter()
+        .map(|file| file.path.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/repo/certora/main.spec",
+            "/repo/common/specs/erc20.spec",
+            "/repo/extra/a.spec"
+        ]
+    );
+
+    assert!("no-equals-sign".parse::<Remapping>().is_err());
+}
+
+#[test]
+fn remappings_match_whole_path_components() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/repo/certora/main.spec",
+        "import \"specs-common-extra/a.spec\";\n",
+    );
+    file_system.insert("/repo/certora/specs-common-extra/a.spec", "rule a { }");
+    file_system.insert("/repo/common/specs-extra/a.spec", "rule wrong { }");
+
+    let remapping = "specs-common=/repo/common/specs"
+        .parse::<Remapping>()
+        .unwrap();
+    let project = ProjectBuilder::new(["/repo/certora/main.spec"])
+        .with_remappings([remapping])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+
+    assert!(project.problems.is_empty());
+    assert_eq!(
+        project.files[1].path.to_str(),
+        Some("/repo/certora/specs-common-extra/a.spec")
+    );
+}
+
+#[test]
+fn in_memory_files_take_precedence_over_the_fallback() {
+    let dir = fixtures();
+    let mut file_system = InMemoryFileSystem::with_fallback(DiskFileSystem);
+    // an unsaved buffer that no longer imports the missing file or the cycle
+    file_system.insert(dir.join("main.spec"), "import \"lib/erc20.spec\";");
+
+    let project = ProjectBuilder::new([dir.join("main.spec")])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+
+    assert!(project.problems.is_empty());
+    assert_eq!(project.files.len(), 3);
+    assert_eq!(project.files[2].path, dir.join("common/shared.spec"));
+}
+
+#[test]
+fn diagnostics_of_every_check() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        indoc! {r#"
+            import "lib.spec";
+            import "missing.spec";
+
+            /**
+             * @notice see {@link nowhere}
+             * @formula counter >= unknownThing
+             * @example
+             * ```
+             * rule r { assert missingFn(); }
+             * ```
+             */
+            rule checks {
+                uint x = oldFee(1);
+                assert counter >= 0;
+            }
+
+            use rule undeclared;
+
+            /// @inheritdoc nothing
+            rule inherits { }
+
+            $%^ garbage
+        "#},
+    );
+    file_system.insert(
+        "/specs/lib.spec",
+        indoc! {"
+            /// @deprecated
+            definition oldFee(uint x) returns uint = x;
+
+            definition neverUsed() returns uint = 1;
+
+            ghost mathint counter;
+        "},
+    );
+
+    let project = ProjectBuilder::new(["/specs/main.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let diagnostics = project
+        .diagnostics(&UnusedConfig::default())
+        .into_iter()
+        .map(|(path, diagnostic)| {
+            let line = diagnostic.range.start.line + 1;
+            format!("{}:{line}: {}", path.display(), diagnostic.message)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            "/specs/main.spec:22: this code could not be parsed",
+            "/specs/main.spec:19: cannot inherit documentation: nothing not found",
+            "/specs/main.spec:5: unresolved link: nowhere",
+            "/specs/main.spec:9: example calls missingFn, which is not declared",
+            "/specs/main.spec:6: unknownThing is not a parameter or a known declaration",
+            "/specs/main.spec:13: oldFee is deprecated",
+            "/specs/main.spec:2: cannot find imported file missing.spec. tried: /specs/missing.spec",
+            "/specs/main.spec:17: no rule named undeclared was found",
+            "/specs/lib.spec:4: definition neverUsed is never used",
+            "/specs/lib.spec:6: ghost counter is read, but is not written by any hook",
+        ]
+    );
+
+    let main = project.file_diagnostics(Path::new("/specs/main.spec"), &UnusedConfig::default());
+    assert_eq!(main.len(), 8);
+    assert!(project
+        .file_diagnostics(Path::new("/specs/other.spec"), &UnusedConfig::default())
+        .is_empty());
+}
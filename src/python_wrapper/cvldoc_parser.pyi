@@ -33,6 +33,11 @@ class TagKind(Enum):
     Param = 3
     Return = 4
     Formula = 5
+    InheritDoc = 6
+    Deprecated = 7
+    Since = 8
+    Example = 9
+    Req = 10
 
 class Span:
     start: int
@@ -50,6 +55,9 @@ class CvlElement:
     def element_name(self) -> Optional[str]: ...
     def element_returns(self) -> Optional[str]: ...
     def element_params(self) -> Optional[List[tuple[str, str]]]: ...
+    def deprecated(self) -> Optional[str]: ...
+    def since(self) -> Optional[str]: ...
+    def signature(self) -> str: ...
 
 def parse(path: Union[str, PathLike]) -> List[CvlElement]: ...
 def parse_string(src: str) -> List[CvlElement]: ...
@@ -32,6 +32,19 @@ impl CvlElementPy {
         self.inner.ast.returns()
     }
 
+    /// the reason the element is deprecated, or `None` if it is not deprecated.
+    pub fn deprecated(&self) -> Option<&str> {
+        self.inner.deprecated()
+    }
+
+    pub fn since(&self) -> Option<&str> {
+        self.inner.since()
+    }
+
+    pub fn signature(&self) -> String {
+        self.inner.ast.signature()
+    }
+
     pub fn element_params(&self) -> Option<Vec<(String, String)>> {
         if let Some(params) = self.inner.ast.params() {
             let params = params
@@ -174,6 +187,11 @@ pub enum TagKindPy {
     Param,
     Return,
     Formula,
+    InheritDoc,
+    Deprecated,
+    Since,
+    Example,
+    Req,
 }
 
 #[pymethods]
@@ -186,6 +204,11 @@ impl TagKindPy {
             TagKindPy::Param => "param",
             TagKindPy::Return => "return",
             TagKindPy::Formula => "formula",
+            TagKindPy::InheritDoc => "inheritdoc",
+            TagKindPy::Deprecated => "deprecated",
+            TagKindPy::Since => "since",
+            TagKindPy::Example => "example",
+            TagKindPy::Req => "req",
         }
     }
 }
@@ -199,6 +222,11 @@ impl From<&TagKind> for TagKindPy {
             TagKind::Param => TagKindPy::Param,
             TagKind::Return => TagKindPy::Return,
             TagKind::Formula => TagKindPy::Formula,
+            TagKind::InheritDoc => TagKindPy::InheritDoc,
+            TagKind::Deprecated => TagKindPy::Deprecated,
+            TagKind::Since => TagKindPy::Since,
+            TagKind::Example => TagKindPy::Example,
+            TagKind::Req => TagKindPy::Req,
         }
     }
 }
@@ -0,0 +1,156 @@
+//! a lightweight scan for identifiers referenced in the code of an element.
+//! this does not parse CVL expressions, so it over-approximates:
+//! every identifier outside of comments and strings is reported.
+
+use crate::util::{ByteSpan, Span};
+use crate::CvlElement;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+static IDENT_PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*").unwrap());
+
+/// suffixes that may appear between the name of a called function and its arguments
+const CALL_MODIFIERS: [&str; 3] = ["@withrevert", "@norevert", "@dontsummarize"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Reference {
+    /// the referenced name. may contain dots, as in `e.msg.sender` or `token.balanceOf`.
+    pub name: String,
+    pub span: Span,
+    /// whether the name is followed by an argument list
+    pub is_call: bool,
+}
+
+impl Reference {
+    /// the part of the name before the first dot
+    pub fn head(&self) -> &str {
+        self.name.split('.').next().unwrap_or(&self.name)
+    }
+}
+
+/// replaces the contents of comments and string literals with spaces,
+/// preserving the number of chars and all newlines.
+fn mask_comments_and_strings(text: &str) -> String {
+    #[derive(Clone, Copy)]
+    enum State {
+        Code,
+        LineComment,
+        BlockComment,
+        String,
+    }
+
+    let mut masked = String::with_capacity(text.len());
+    let mut state = State::Code;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        let next = chars.peek().copied();
+
+        let (masked_ch, next_state) = match state {
+            State::Code => match (ch, next) {
+                ('/', Some('/')) => (' ', State::LineComment),
+                ('/', Some('*')) => {
+                    // consume the `*` as well, so that `/*/` is not considered a full comment
+                    chars.next();
+                    masked.push(' ');
+                    (' ', State::BlockComment)
+                }
+                ('"', _) => (' ', State::String),
+                _ => (ch, State::Code),
+            },
+            State::LineComment if ch == '\n' => (ch, State::Code),
+            State::BlockComment if ch == '*' && next == Some('/') => {
+                chars.next();
+                masked.push(' ');
+                (' ', State::Code)
+            }
+            State::String if ch == '"' => (' ', State::Code),
+            inside => {
+                let masked_ch = if ch == '\n' { ch } else { ' ' };
+                (masked_ch, inside)
+            }
+        };
+
+        masked.push(masked_ch);
+        state = next_state;
+    }
+
+    masked
+}
+
+/// all identifiers in `text`, with spans offset by `offset` chars
+pub fn references_in(text: &str, offset: usize) -> Vec<Reference> {
+    let masked = mask_comments_and_strings(text);
+
+    let mut references = Vec::new();
+    let mut chars_before = 0;
+    let mut bytes_before = 0;
+
+    for found in IDENT_PATH.find_iter(&masked) {
+        chars_before += masked[bytes_before..found.start()].chars().count();
+        bytes_before = found.start();
+
+        let preceded_by_ident_char = masked[..found.start()]
+            .chars()
+            .next_back()
+            .is_some_and(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '$' | '.' | '@'));
+        if preceded_by_ident_char {
+            continue;
+        }
+
+        let rest = masked[found.end()..].trim_start();
+        let rest = CALL_MODIFIERS
+            .iter()
+            .find_map(|modifier| rest.strip_prefix(modifier))
+            .unwrap_or(rest);
+        let is_call = rest.trim_start().starts_with('(');
+
+        let start = offset + chars_before;
+        let end = start + found.as_str().chars().count();
+
+        references.push(Reference {
+            name: found.as_str().to_string(),
+            span: start..end,
+            is_call,
+        });
+    }
+
+    references
+}
+
+/// whether the identifier that ends at char `end` of `code` is the target of an assignment,
+/// such as `g = x` or `g[a][b] = x`. `code` is the source of a single element, so that
+/// the search stops at the end of the element.
+pub(crate) fn is_assigned_in(code: &str, end: usize) -> bool {
+    let mut rest = code.chars().skip(end).peekable();
+    let mut depth = 0usize;
+
+    while let Some(ch) = rest.next() {
+        match ch {
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            _ if depth > 0 || ch.is_whitespace() => {}
+            // `==` is a comparison and `=>` an implication
+            '=' => return !matches!(rest.peek(), Some('=' | '>')),
+            _ => return false,
+        }
+    }
+
+    false
+}
+
+impl CvlElement {
+    /// all identifiers in the code of this element, excluding its documentation.
+    /// this includes the names in its own declaration.
+    pub fn references(&self) -> Vec<Reference> {
+        match self.element_span.byte_slice(&self.src) {
+            Some(text) => references_in(text, self.element_span.start),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use super::references_in;
+use crate::parse::builder::Builder;
+use crate::util::ByteSpan;
+use indoc::indoc;
+use itertools::Itertools;
+
+#[test]
+fn identifiers_and_calls() {
+    let text = indoc! {r#"
+        rule foo(address a) {
+            // ignored(x)
+            /* also ignored */ env e;
+            f@withrevert(e, "not an ident");
+            assert balanceOf(a)<=totalSupply() && e.msg.sender != 0x1ab, "msg";
+            require🔥 ghostMap[a] == token.balanceOf (a);
+        }
+    "#};
+
+    let references = references_in(text, 0);
+    let summary = references
+        .iter()
+        .map(|reference| (reference.name.as_str(), reference.is_call))
+        .collect_vec();
+
+    assert_eq!(
+        summary,
+        [
+            ("rule", false),
+            ("foo", true),
+            ("address", false),
+            ("a", false),
+            ("env", false),
+            ("e", false),
+            ("f", true),
+            ("e", false),
+            ("assert", false),
+            ("balanceOf", true),
+            ("a", false),
+            ("totalSupply", true),
+            ("e.msg.sender", false),
+            ("require", false),
+            ("ghostMap", false),
+            ("a", false),
+            ("token.balanceOf", true),
+            ("a", false),
+        ]
+    );
+
+    let token_balance_of = &references[16];
+    assert_eq!(token_balance_of.head(), "token");
+    assert_eq!(
+        token_balance_of.span.byte_slice(text),
+        Some("token.balanceOf")
+    );
+}
+
+#[test]
+fn element_references_exclude_documentation() {
+    let src = indoc! {"
+        /// see helper()
+        rule foo { helper(); }
+    "};
+
+    let element = Builder::new(src).build().unwrap().remove(0);
+    let references = element.references();
+    let names = references
+        .iter()
+        .map(|reference| reference.name.as_str())
+        .collect_vec();
+
+    assert_eq!(names, ["rule", "foo", "helper"]);
+    assert_eq!(references[2].span.byte_slice(src), Some("helper"));
+}
@@ -0,0 +1,274 @@
+//! traceability of requirements to the rules and invariants that cover them.
+//!
+//! an element covers a requirement with a `@req <ID>` tag, or equivalently `@custom:req <ID>`.
+//! the ID may be followed by a description, and the tag may appear any number of times.
+
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, CvlElement, TagKind};
+use itertools::Itertools;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[cfg(test)]
+mod tests;
+
+/// IDs such as `REQ-12`, `SEC.3.1` or `transfer_1`
+static REQUIREMENT_ID: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9]+(?:[-_.:][A-Za-z0-9]+)*$").unwrap());
+
+/// a requirement covered by an element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement<'a> {
+    pub id: &'a str,
+    /// the span of the tag
+    pub span: Span,
+}
+
+/// a `@req` tag whose ID is missing or malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedRequirement {
+    pub id: String,
+    pub span: Span,
+}
+
+impl MalformedRequirement {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let message = if self.id.is_empty() {
+            "requirement ID is missing".to_string()
+        } else {
+            format!("malformed requirement ID: {}", self.id)
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// a required ID, read from a list of requirements by [required_ids]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredId<'r> {
+    pub id: &'r str,
+    /// the span of the ID in the list
+    pub span: Span,
+}
+
+/// reads a list of required IDs, one per line. as in a `@req` tag, the ID may be followed
+/// by a description. blank lines, and lines starting with `#`, are skipped.
+pub fn required_ids(list: &str) -> Vec<RequiredId<'_>> {
+    let mut required = Vec::new();
+    let mut line_start = 0;
+
+    for line in list.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let id = trimmed.split_whitespace().next().unwrap_or_default();
+
+        if !id.is_empty() && !id.starts_with('#') {
+            let indent = line.len() - trimmed.len();
+            let start = line_start + line[..indent].chars().count();
+            required.push(RequiredId {
+                id,
+                span: start..start + id.chars().count(),
+            });
+        }
+
+        line_start += line.chars().count();
+    }
+
+    required
+}
+
+/// a required ID that no element covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncoveredRequirement {
+    pub id: String,
+    /// the span of the ID in the list of requirements that declares it
+    pub span: Span,
+}
+
+impl UncoveredRequirement {
+    /// the diagnostic is placed at the ID in the list of requirements, so `converter`
+    /// should be that of the list
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            message: format!(
+                "requirement {} is not covered by any rule or invariant",
+                self.id
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+impl CvlElement {
+    /// the requirements this element covers, and the `@req` tags with malformed IDs
+    pub fn requirements(&self) -> (Vec<Requirement<'_>>, Vec<MalformedRequirement>) {
+        let mut requirements = Vec::new();
+        let mut malformed = Vec::new();
+
+        for tag in self.doc.iter().filter(|tag| tag.kind == TagKind::Req) {
+            let id = tag
+                .description
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+
+            if REQUIREMENT_ID.is_match(id) {
+                requirements.push(Requirement {
+                    id,
+                    span: tag.span.clone(),
+                });
+            } else {
+                malformed.push(MalformedRequirement {
+                    id: id.to_string(),
+                    span: tag.span.clone(),
+                });
+            }
+        }
+
+        (requirements, malformed)
+    }
+}
+
+/// an element that covers a requirement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage<'a> {
+    /// the file the element is in, as given to [RequirementMatrix::new]
+    pub file: &'a str,
+    pub element: &'a CvlElement,
+}
+
+impl Coverage<'_> {
+    /// the name of the element, or of the rule or invariant it uses
+    pub fn name(&self) -> &str {
+        match &self.element.ast {
+            Ast::UseRule { name, .. } | Ast::UseInvariant { name, .. } => name,
+            ast => ast.name().unwrap_or_default(),
+        }
+    }
+
+    /// the kind of the element. `@req` is only supported on rules, invariants and their
+    /// `use` statements, but elements of other kinds that have the tag are still listed.
+    pub fn kind(&self) -> &str {
+        match self.element.ast {
+            Ast::Rule { .. } => "rule",
+            Ast::Invariant { .. } => "invariant",
+            Ast::UseRule { .. } => "use rule",
+            Ast::UseInvariant { .. } => "use invariant",
+            Ast::UseBuiltinRule { .. } => "use builtin rule",
+            Ast::Function { .. } => "function",
+            Ast::Definition { .. } => "definition",
+            Ast::GhostFunction { .. } => "ghost function",
+            Ast::GhostMapping { .. } => "ghost mapping",
+            Ast::Methods { .. } => "methods",
+            Ast::Import { .. } => "import",
+            Ast::Using { .. } => "using",
+            Ast::FreeFormComment { .. } => "freeform comment",
+            Ast::HookSload { .. }
+            | Ast::HookSstore { .. }
+            | Ast::HookCreate { .. }
+            | Ast::HookOpcode { .. } => "hook",
+        }
+    }
+}
+
+/// the elements covering each requirement, over a set of files
+#[derive(Debug, Clone, Default)]
+pub struct RequirementMatrix<'a> {
+    /// the elements covering each requirement, ordered by requirement ID
+    pub rows: BTreeMap<&'a str, Vec<Coverage<'a>>>,
+    pub malformed: Vec<(&'a str, MalformedRequirement)>,
+}
+
+impl<'a> RequirementMatrix<'a> {
+    /// builds the matrix from the elements of each file, given with the name of the file
+    pub fn new(files: &[(&'a str, &'a [CvlElement])]) -> RequirementMatrix<'a> {
+        let mut matrix = RequirementMatrix::default();
+
+        for &(file, elements) in files {
+            for element in elements {
+                let (requirements, malformed) = element.requirements();
+
+                for requirement in requirements {
+                    let coverage = Coverage { file, element };
+                    let row = matrix.rows.entry(requirement.id).or_default();
+                    if !row.contains(&coverage) {
+                        row.push(coverage);
+                    }
+                }
+
+                matrix
+                    .malformed
+                    .extend(malformed.into_iter().map(|malformed| (file, malformed)));
+            }
+        }
+
+        matrix
+    }
+
+    /// the IDs in `required` that are not covered by any element
+    pub fn uncovered(&self, required: &[RequiredId]) -> Vec<UncoveredRequirement> {
+        required
+            .iter()
+            .filter(|required| !self.rows.contains_key(required.id))
+            .map(|required| UncoveredRequirement {
+                id: required.id.to_string(),
+                span: required.span.clone(),
+            })
+            .collect()
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (&str, &Coverage<'_>)> {
+        self.rows
+            .iter()
+            .flat_map(|(id, row)| row.iter().map(move |coverage| (*id, coverage)))
+    }
+
+    /// one line per covering element, with a header line
+    pub fn to_csv(&self) -> String {
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+
+        let mut csv = String::from("requirement,element,kind,file\n");
+        for (id, coverage) in self.entries() {
+            let line = [id, coverage.name(), coverage.kind(), coverage.file]
+                .map(field)
+                .join(",");
+            writeln!(csv, "{line}").unwrap();
+        }
+
+        csv
+    }
+
+    /// a Markdown table with one row per covering element
+    pub fn to_markdown(&self) -> String {
+        fn cell(value: &str) -> String {
+            value.replace('|', "\\|")
+        }
+
+        let mut markdown =
+            String::from("| Requirement | Element | Kind | File |\n| --- | --- | --- | --- |\n");
+        for (id, coverage) in self.entries() {
+            let row = [id, coverage.name(), coverage.kind(), coverage.file]
+                .map(cell)
+                .iter()
+                .join(" | ");
+            writeln!(markdown, "| {row} |").unwrap();
+        }
+
+        markdown
+    }
+}
@@ -0,0 +1,184 @@
+use super::{required_ids, RequirementMatrix};
+use crate::parse::builder::Builder;
+use crate::util::{ByteSpan, RangeConverter};
+use crate::{CvlElement, TagKind};
+use indoc::indoc;
+use lsp_types::DiagnosticSeverity;
+use ropey::Rope;
+
+const TOKEN_SRC: &str = indoc! {"
+    /// @req TOK-1 transfers preserve the total supply
+    /// @custom:req TOK-2
+    rule transferPreservesSupply { }
+
+    /// @req TOK-2
+    /// @req not,valid
+    invariant solvency() true;
+"};
+
+const MAIN_SRC: &str = indoc! {"
+    /// @req TOK-1
+    use rule transferPreservesSupply;
+
+    /// @req
+    rule unrelated { }
+
+    /// @req TOK-3 not a rule, but still listed
+    definition fee(uint x) returns uint = x / 100;
+"};
+
+fn parse(src: &str) -> Vec<CvlElement> {
+    Builder::new(src).build().unwrap()
+}
+
+#[test]
+fn requirements_are_collected_into_a_matrix() {
+    let token = parse(TOKEN_SRC);
+    let main = parse(MAIN_SRC);
+
+    assert_eq!(token[0].doc[1].kind, TagKind::Req);
+    assert_eq!(token[0].doc[1].description, "TOK-2");
+
+    let matrix = RequirementMatrix::new(&[("token.spec", &token), ("main.spec", &main)]);
+
+    let ids = matrix.rows.keys().copied().collect::<Vec<_>>();
+    assert_eq!(ids, ["TOK-1", "TOK-2", "TOK-3"]);
+    assert_eq!(matrix.rows["TOK-1"].len(), 2);
+
+    let kinds = matrix
+        .entries()
+        .map(|(id, coverage)| (id, coverage.name(), coverage.kind()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("TOK-1", "transferPreservesSupply", "rule"),
+            ("TOK-1", "transferPreservesSupply", "use rule"),
+            ("TOK-2", "transferPreservesSupply", "rule"),
+            ("TOK-2", "solvency", "invariant"),
+            ("TOK-3", "fee", "definition"),
+        ]
+    );
+}
+
+#[test]
+fn matrix_as_csv() {
+    let token = parse(TOKEN_SRC);
+    let main = parse(MAIN_SRC);
+    let matrix = RequirementMatrix::new(&[("token.spec", &token), ("specs/main, v2.spec", &main)]);
+
+    assert_eq!(
+        matrix.to_csv(),
+        indoc! {r#"
+            requirement,element,kind,file
+            TOK-1,transferPreservesSupply,rule,token.spec
+            TOK-1,transferPreservesSupply,use rule,"specs/main, v2.spec"
+            TOK-2,transferPreservesSupply,rule,token.spec
+            TOK-2,solvency,invariant,token.spec
+            TOK-3,fee,definition,"specs/main, v2.spec"
+        "#}
+    );
+
+    let quoted = RequirementMatrix::new(&[(r#"the "main" spec"#, &main)]);
+    let csv = quoted.to_csv();
+    assert_eq!(
+        csv.lines().nth(1),
+        Some(r#"TOK-1,transferPreservesSupply,use rule,"the ""main"" spec""#)
+    );
+
+    let empty = RequirementMatrix::new(&[]);
+    assert_eq!(empty.to_csv(), "requirement,element,kind,file\n");
+}
+
+#[test]
+fn matrix_as_markdown() {
+    let token = parse(TOKEN_SRC);
+    let main = parse(MAIN_SRC);
+    let matrix = RequirementMatrix::new(&[("token.spec", &token), ("main|v2.spec", &main)]);
+
+    assert_eq!(
+        matrix.to_markdown(),
+        indoc! {r"
+            | Requirement | Element | Kind | File |
+            | --- | --- | --- | --- |
+            | TOK-1 | transferPreservesSupply | rule | token.spec |
+            | TOK-1 | transferPreservesSupply | use rule | main\|v2.spec |
+            | TOK-2 | transferPreservesSupply | rule | token.spec |
+            | TOK-2 | solvency | invariant | token.spec |
+            | TOK-3 | fee | definition | main\|v2.spec |
+        "}
+    );
+
+    let empty = RequirementMatrix::new(&[]);
+    assert_eq!(
+        empty.to_markdown(),
+        "| Requirement | Element | Kind | File |\n| --- | --- | --- | --- |\n"
+    );
+}
+
+#[test]
+fn malformed_requirements() {
+    let token = parse(TOKEN_SRC);
+    let main = parse(MAIN_SRC);
+    let matrix = RequirementMatrix::new(&[("token.spec", &token), ("main.spec", &main)]);
+
+    let malformed = matrix
+        .malformed
+        .iter()
+        .map(|(file, malformed)| (*file, malformed.id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(malformed, [("token.spec", "not,valid"), ("main.spec", "")]);
+
+    let converter = RangeConverter::new(Rope::from_str(TOKEN_SRC));
+    let (_, not_valid) = &matrix.malformed[0];
+    assert_eq!(
+        not_valid.span.byte_slice(TOKEN_SRC),
+        Some("/// @req not,valid\n")
+    );
+    let diagnostic = not_valid.to_diagnostic(&converter);
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostic.message, "malformed requirement ID: not,valid");
+    assert_eq!(diagnostic.range.start.line, 5);
+
+    let converter = RangeConverter::new(Rope::from_str(MAIN_SRC));
+    let (_, missing) = &matrix.malformed[1];
+    let diagnostic = missing.to_diagnostic(&converter);
+    assert_eq!(diagnostic.message, "requirement ID is missing");
+    assert_eq!(diagnostic.range.start.line, 3);
+}
+
+#[test]
+fn uncovered_requirements() {
+    let token = parse(TOKEN_SRC);
+    let matrix = RequirementMatrix::new(&[("token.spec", &token)]);
+
+    let list = indoc! {"
+        # requirements of the token
+        TOK-1 transfers preserve the total supply
+
+          TOK-4 mints are bounded
+        TOK-2
+    "};
+    let required = required_ids(list);
+    let ids = required
+        .iter()
+        .map(|required| required.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["TOK-1", "TOK-4", "TOK-2"]);
+
+    let uncovered = matrix.uncovered(&required);
+    assert_eq!(uncovered.len(), 1);
+    assert_eq!(uncovered[0].id, "TOK-4");
+    assert_eq!(uncovered[0].span.byte_slice(list), Some("TOK-4"));
+
+    let converter = RangeConverter::new(Rope::from_str(list));
+    let diagnostic = uncovered[0].to_diagnostic(&converter);
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(
+        diagnostic.message,
+        "requirement TOK-4 is not covered by any rule or invariant"
+    );
+    assert_eq!(diagnostic.range.start.line, 3);
+    assert_eq!(diagnostic.range.start.character, 2);
+    assert_eq!(diagnostic.range.end.character, 7);
+}
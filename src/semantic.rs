@@ -0,0 +1,230 @@
+//! checks that need the declarations of every imported file, in addition to the file itself.
+
+use crate::project::SpecProject;
+use crate::symbols::{Symbol, SymbolKind, SymbolTable};
+use crate::util::{RangeConverter, Span};
+use crate::{Ast, CvlElement};
+use itertools::Itertools;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticProblemKind {
+    /// `use rule X`, where no rule named X is declared or imported
+    UnknownRule(String),
+    /// `use invariant X`, where no invariant named X is declared or imported
+    UnknownInvariant(String),
+    /// `requireInvariant X(...)`, where no invariant named X is declared or imported
+    UnknownRequiredInvariant(String),
+    /// a rule, invariant, definition or ghost with the same name as another declaration
+    Duplicate {
+        name: String,
+        /// where the other declaration is, as `path:line`
+        other: String,
+    },
+    /// a `using` alias that is also declared for a different contract
+    ConflictingUsing {
+        alias: String,
+        contract: String,
+        other_contract: String,
+    },
+}
+
+/// a problem in a file of a [SpecProject]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticProblem {
+    /// the index of the file in [SpecProject::files]
+    pub file: usize,
+    pub span: Span,
+    pub kind: SemanticProblemKind,
+}
+
+impl SemanticProblem {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let message = match &self.kind {
+            SemanticProblemKind::UnknownRule(name) => format!("no rule named {name} was found"),
+            SemanticProblemKind::UnknownInvariant(name)
+            | SemanticProblemKind::UnknownRequiredInvariant(name) => {
+                format!("no invariant named {name} was found")
+            }
+            SemanticProblemKind::Duplicate { name, other } => {
+                format!("{name} is also declared at {other}")
+            }
+            SemanticProblemKind::ConflictingUsing {
+                alias,
+                contract,
+                other_contract,
+            } => format!("{alias} is an alias of {contract}, but also of {other_contract}"),
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// the span of the name in the declaration of `element`, or else the span of the whole element
+pub(crate) fn name_span(element: &CvlElement, name: &str) -> Span {
+    element
+        .references()
+        .into_iter()
+        .find(|reference| reference.name == name)
+        .map(|reference| reference.span)
+        .unwrap_or_else(|| element.element_span.clone())
+}
+
+/// whether two declarations with the same name conflict. ghost functions and ghost mappings
+/// share a namespace, and functions may be overloaded.
+fn conflicts(a: &Symbol, b: &Symbol) -> bool {
+    let namespace = |kind| match kind {
+        SymbolKind::GhostFunction | SymbolKind::GhostMapping => Some(SymbolKind::GhostMapping),
+        SymbolKind::Function | SymbolKind::Using => None,
+        kind => Some(kind),
+    };
+
+    namespace(a.kind).is_some() && namespace(a.kind) == namespace(b.kind)
+}
+
+impl SpecProject {
+    fn file_of(&self, symbol: &Symbol) -> Option<usize> {
+        self.files
+            .iter()
+            .position(|file| file.path.to_str() == Some(symbol.id.path.as_str()))
+    }
+
+    fn location(&self, file: usize, span: &Span) -> String {
+        let file = &self.files[file];
+        let line = file.converter().to_range(span.clone()).start.line + 1;
+        format!("{}:{line}", file.path.display())
+    }
+
+    /// problems that can only be found with the declarations of imported files.
+    /// each file is checked against its own declarations and those of the files it imports.
+    pub fn semantic_problems(&self) -> Vec<SemanticProblem> {
+        let mut problems = Vec::new();
+        // declarations that are visible from several files are only reported once
+        let mut reported: HashSet<(usize, Span)> = HashSet::new();
+
+        for (index, file) in self.files.iter().enumerate() {
+            let table = SymbolTable::for_file(self, index);
+            let has =
+                |name: &str, kind| table.lookup(name).iter().any(|symbol| symbol.kind == kind);
+
+            for element in &file.spec.elements {
+                let problem = match &element.ast {
+                    Ast::UseRule { name, .. } if !has(name, SymbolKind::Rule) => {
+                        Some(SemanticProblemKind::UnknownRule(name.clone()))
+                    }
+                    Ast::UseInvariant { name, .. } if !has(name, SymbolKind::Invariant) => {
+                        Some(SemanticProblemKind::UnknownInvariant(name.clone()))
+                    }
+                    _ => None,
+                };
+                if let Some(kind) = problem {
+                    problems.push(SemanticProblem {
+                        file: index,
+                        span: element.element_span.clone(),
+                        kind,
+                    });
+                }
+
+                for (keyword, required) in element.references().into_iter().tuple_windows() {
+                    if keyword.name == "requireInvariant"
+                        && !has(&required.name, SymbolKind::Invariant)
+                    {
+                        problems.push(SemanticProblem {
+                            file: index,
+                            span: required.span,
+                            kind: SemanticProblemKind::UnknownRequiredInvariant(required.name),
+                        });
+                    }
+                }
+            }
+
+            let names = table.symbols().iter().map(|symbol| symbol.name).unique();
+
+            for symbols in names.map(|name| table.lookup(name)) {
+                for (symbol, other) in symbols.iter().tuple_combinations() {
+                    for (symbol, other) in [(symbol, other), (other, symbol)] {
+                        let Some(symbol_file) = self.file_of(symbol) else {
+                            continue;
+                        };
+
+                        let kind = match (&symbol.element.ast, &other.element.ast) {
+                            (
+                                Ast::Using {
+                                    contract_name,
+                                    spec_name,
+                                },
+                                Ast::Using {
+                                    contract_name: other_contract,
+                                    ..
+                                },
+                            ) if contract_name != other_contract => {
+                                SemanticProblemKind::ConflictingUsing {
+                                    alias: spec_name.clone(),
+                                    contract: contract_name.clone(),
+                                    other_contract: other_contract.clone(),
+                                }
+                            }
+                            _ if conflicts(symbol, other) => {
+                                let Some(other_file) = self.file_of(other) else {
+                                    continue;
+                                };
+                                let other_span = name_span(other.element, other.name);
+                                SemanticProblemKind::Duplicate {
+                                    name: symbol.name.to_string(),
+                                    other: self.location(other_file, &other_span),
+                                }
+                            }
+                            _ => continue,
+                        };
+
+                        let span = match &symbol.element.ast {
+                            Ast::Using { .. } => symbol.element.element_span.clone(),
+                            _ => name_span(symbol.element, symbol.name),
+                        };
+                        if reported.insert((symbol_file, span.clone())) {
+                            problems.push(SemanticProblem {
+                                file: symbol_file,
+                                span,
+                                kind,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// the diagnostics of [SpecProject::semantic_problems], with the path of the file each belongs to
+    pub fn semantic_diagnostics(&self) -> Vec<(&Path, Diagnostic)> {
+        let problems = self.semantic_problems();
+        let converters: HashMap<usize, RangeConverter> = problems
+            .iter()
+            .map(|problem| problem.file)
+            .unique()
+            .map(|file| (file, self.files[file].converter()))
+            .collect();
+
+        problems
+            .iter()
+            .map(|problem| {
+                let file = &self.files[problem.file];
+                (
+                    file.path.as_path(),
+                    problem.to_diagnostic(&converters[&problem.file]),
+                )
+            })
+            .collect()
+    }
+}
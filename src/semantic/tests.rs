@@ -0,0 +1,167 @@
+use super::SemanticProblemKind;
+use crate::project::fs::InMemoryFileSystem;
+use crate::project::ProjectBuilder;
+use crate::util::ByteSpan;
+use assert_matches::assert_matches;
+use indoc::indoc;
+
+#[test]
+fn dangling_uses_and_required_invariants() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        indoc! {"
+            import \"lib.spec\";
+
+            use rule transfer;
+            use rule mint;
+            use invariant solvent;
+            use invariant bounded;
+
+            rule r(env e) {
+                requireInvariant solvent();
+                requireInvariant positive(e.msg.sender);
+                assert true;
+            }
+        "},
+    );
+    file_system.insert(
+        "/specs/lib.spec",
+        indoc! {"
+            rule transfer { assert true; }
+            invariant solvent() true;
+        "},
+    );
+
+    let project = ProjectBuilder::new(["/specs/main.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let problems = project.semantic_problems();
+    let src = &project.files[0].src;
+
+    let found = problems
+        .iter()
+        .map(|problem| (problem.span.byte_slice(src).unwrap(), problem.kind.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (
+                "use rule mint;",
+                SemanticProblemKind::UnknownRule("mint".to_string())
+            ),
+            (
+                "use invariant bounded;",
+                SemanticProblemKind::UnknownInvariant("bounded".to_string())
+            ),
+            (
+                "positive",
+                SemanticProblemKind::UnknownRequiredInvariant("positive".to_string())
+            ),
+        ]
+    );
+
+    let diagnostics = project.semantic_diagnostics();
+    assert_eq!(diagnostics[2].0.to_str(), Some("/specs/main.spec"));
+    assert_eq!(diagnostics[2].1.range.start.line, 9);
+    assert_eq!(
+        diagnostics[2].1.message,
+        "no invariant named positive was found"
+    );
+}
+
+#[test]
+fn duplicates_across_the_import_graph_are_reported_once() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        indoc! {"
+            import \"a.spec\";
+            import \"b.spec\";
+
+            function f(uint x) returns uint { return x; }
+            function f(uint x, uint y) returns uint { return x; }
+        "},
+    );
+    file_system.insert(
+        "/specs/a.spec",
+        indoc! {"
+            ghost mapping(address => uint) balances;
+            definition MAX() returns uint = 10;
+            rule r { assert true; }
+        "},
+    );
+    file_system.insert(
+        "/specs/b.spec",
+        indoc! {"
+            ghost balances(address) returns uint;
+            rule r { assert true; }
+        "},
+    );
+
+    let project = ProjectBuilder::new(["/specs/main.spec", "/specs/b.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let problems = project.semantic_problems();
+
+    // overloaded functions are not duplicates
+    let found = problems
+        .iter()
+        .map(|problem| {
+            let file = &project.files[problem.file];
+            let text = problem.span.byte_slice(&file.src).unwrap();
+            (file.path.to_str().unwrap(), text)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            ("/specs/a.spec", "balances"),
+            ("/specs/b.spec", "balances"),
+            ("/specs/a.spec", "r"),
+            ("/specs/b.spec", "r"),
+        ]
+    );
+    assert_matches!(
+        &problems[0].kind,
+        SemanticProblemKind::Duplicate { name, other } if name == "balances" && other == "/specs/b.spec:1"
+    );
+}
+
+#[test]
+fn conflicting_using_aliases() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        "import \"lib.spec\";\nusing ERC20 as token;\nusing ERC721 as nft;\n",
+    );
+    file_system.insert(
+        "/specs/lib.spec",
+        "using WETH as token;\nusing ERC721 as nft;\n",
+    );
+
+    let project = ProjectBuilder::new(["/specs/main.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let problems = project.semantic_problems();
+
+    // the same alias of the same contract is not a conflict
+    assert_eq!(problems.len(), 2);
+    assert_eq!(problems[0].file, 0);
+    assert_eq!(
+        problems[0].kind,
+        SemanticProblemKind::ConflictingUsing {
+            alias: "token".to_string(),
+            contract: "ERC20".to_string(),
+            other_contract: "WETH".to_string(),
+        }
+    );
+    assert_eq!(problems[1].file, 1);
+    assert_eq!(
+        problems[1].span.byte_slice(&project.files[1].src),
+        Some("using WETH as token;")
+    );
+}
@@ -0,0 +1,177 @@
+//! a language server that publishes the diagnostics of open documents.
+//! see the `cvldoc-lsp` binary, which runs it over stdio.
+
+use crate::project::fs::{DiskFileSystem, InMemoryFileSystem};
+use crate::project::ProjectBuilder;
+use crate::unused::UnusedConfig;
+use color_eyre::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, LogMessage,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, InitializeResult, LogMessageParams, MessageType,
+    PublishDiagnosticsParams, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        ..Default::default()
+    }
+}
+
+/// the diagnostics of the document at `path`, as given by [crate::project::SpecProject::file_diagnostics].
+/// the document, and the files it imports, are read from `file_system`.
+pub fn document_diagnostics(path: &Path, file_system: InMemoryFileSystem) -> Vec<Diagnostic> {
+    let project = ProjectBuilder::new([path])
+        .with_file_system(file_system)
+        .build();
+
+    match project {
+        Ok(project) => project.file_diagnostics(path, &UnusedConfig::default()),
+        Err(error) => vec![Diagnostic {
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: format!("failed to parse the document: {error}"),
+            ..Default::default()
+        }],
+    }
+}
+
+/// the path of the file at `uri`. documents that are not files, such as unsaved buffers,
+/// are given the path of their URI.
+fn document_path(uri: &Url) -> PathBuf {
+    uri.to_file_path()
+        .unwrap_or_else(|()| PathBuf::from(uri.path()))
+}
+
+/// shows a message in the log of the client
+fn log_message(connection: &Connection, typ: MessageType, message: String) -> Result<()> {
+    let params = LogMessageParams { typ, message };
+    let notification = Notification::new(LogMessage::METHOD.to_string(), params);
+    connection.sender.send(notification.into())?;
+    Ok(())
+}
+
+/// the open documents, by their URI
+#[derive(Debug, Default)]
+struct Server {
+    documents: HashMap<Url, String>,
+}
+
+impl Server {
+    /// the open documents, which take precedence over the files on disk
+    fn file_system(&self) -> InMemoryFileSystem {
+        let mut file_system = InMemoryFileSystem::with_fallback(DiskFileSystem);
+        for (uri, src) in &self.documents {
+            file_system.insert(document_path(uri), src.clone());
+        }
+        file_system
+    }
+
+    /// publishes the diagnostics of a document, or clears them if it is no longer open
+    fn publish(&self, connection: &Connection, uri: Url, version: Option<i32>) -> Result<()> {
+        let diagnostics = match self.documents.contains_key(&uri) {
+            true => document_diagnostics(&document_path(&uri), self.file_system()),
+            false => Vec::new(),
+        };
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn handle_notification(
+        &mut self,
+        connection: &Connection,
+        notification: Notification,
+    ) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                self.publish(connection, document.uri, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                // with full sync, the last change holds the whole document
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), change.text);
+                self.publish(connection, document.uri, Some(document.version))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.publish(connection, uri, None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_request(&self, connection: &Connection, request: Request) -> Result<()> {
+        let response = Response::new_err(
+            request.id,
+            ErrorCode::MethodNotFound as i32,
+            format!("unsupported request: {}", request.method),
+        );
+        connection.sender.send(response.into())?;
+        Ok(())
+    }
+}
+
+/// answers `initialize`, then handles messages until the client shuts the server down
+pub fn run(connection: Connection) -> Result<()> {
+    let (id, _params) = connection.initialize_start()?;
+    let result = InitializeResult {
+        capabilities: capabilities(),
+        server_info: Some(ServerInfo {
+            name: "cvldoc-lsp".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    };
+    connection.initialize_finish(id, serde_json::to_value(result)?)?;
+
+    let mut server = Server::default();
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.handle_request(&connection, request)?;
+            }
+            Message::Notification(notification) => {
+                let method = notification.method.clone();
+                // a malformed notification is reported, without stopping the server
+                if let Err(error) = server.handle_notification(&connection, notification) {
+                    let message = format!("failed to handle {method}: {error}");
+                    log_message(&connection, MessageType::ERROR, message)?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use super::run;
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Initialized,
+    LogMessage, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Initialize, Request as _, Shutdown};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    InitializeParams, InitializeResult, LogMessageParams, MessageType, PublishDiagnosticsParams,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url, VersionedTextDocumentIdentifier,
+};
+use serde::de::DeserializeOwned;
+use std::thread;
+
+fn notify(client: &Connection, method: &str, params: impl serde::Serialize) {
+    let notification = Notification::new(method.to_string(), params);
+    client.sender.send(notification.into()).unwrap();
+}
+
+fn request(client: &Connection, id: i32, method: &str, params: impl serde::Serialize) {
+    let request = Request::new(RequestId::from(id), method.to_string(), params);
+    client.sender.send(request.into()).unwrap();
+}
+
+fn receive_response<T: DeserializeOwned>(client: &Connection) -> T {
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => serde_json::from_value(response.result.unwrap()).unwrap(),
+        message => panic!("expected a response, got {message:?}"),
+    }
+}
+
+fn receive_diagnostics(client: &Connection) -> PublishDiagnosticsParams {
+    match client.receiver.recv().unwrap() {
+        Message::Notification(notification) => {
+            assert_eq!(notification.method, PublishDiagnostics::METHOD);
+            serde_json::from_value(notification.params).unwrap()
+        }
+        message => panic!("expected a notification, got {message:?}"),
+    }
+}
+
+#[test]
+fn diagnostics_are_published_for_open_documents() {
+    let (server, client) = Connection::memory();
+    let server = thread::spawn(move || run(server));
+
+    request(&client, 1, Initialize::METHOD, InitializeParams::default());
+    let initialized: InitializeResult = receive_response(&client);
+    assert_eq!(
+        initialized.capabilities.text_document_sync,
+        Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL))
+    );
+    assert_eq!(initialized.server_info.unwrap().name, "cvldoc-lsp");
+    notify(&client, Initialized::METHOD, serde_json::json!({}));
+
+    let uri = Url::parse("file:///specs/token.spec").unwrap();
+    let text = "/// @param y no such parameter\nrule r(uint x) { assert true; }\n";
+    notify(
+        &client,
+        DidOpenTextDocument::METHOD,
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "cvl".to_string(),
+                1,
+                text.to_string(),
+            ),
+        },
+    );
+    let published = receive_diagnostics(&client);
+    assert_eq!(published.uri, uri);
+    assert_eq!(published.version, Some(1));
+    assert_eq!(published.diagnostics.len(), 1);
+    assert_eq!(published.diagnostics[0].message, "no such parameter: y");
+    assert_eq!(published.diagnostics[0].range.start.line, 0);
+
+    let fixed = "/// @param x a parameter\nrule r(uint x) { assert true; }\n";
+    notify(
+        &client,
+        DidChangeTextDocument::METHOD,
+        DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: fixed.to_string(),
+            }],
+        },
+    );
+    let published = receive_diagnostics(&client);
+    assert_eq!(published.version, Some(2));
+    assert!(published.diagnostics.is_empty());
+
+    notify(
+        &client,
+        DidCloseTextDocument::METHOD,
+        DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        },
+    );
+    let published = receive_diagnostics(&client);
+    assert_eq!(published.uri, uri);
+    assert!(published.diagnostics.is_empty());
+
+    // unsupported requests are answered with an error
+    request(&client, 2, "textDocument/hover", serde_json::json!({}));
+    let Message::Response(response) = client.receiver.recv().unwrap() else {
+        panic!("expected a response");
+    };
+    assert!(response.error.is_some());
+
+    request(&client, 3, Shutdown::METHOD, ());
+    let () = receive_response(&client);
+    notify(&client, Exit::METHOD, ());
+
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn malformed_notifications_do_not_stop_the_server() {
+    let (server, client) = Connection::memory();
+    let server = thread::spawn(move || run(server));
+
+    request(&client, 1, Initialize::METHOD, InitializeParams::default());
+    let _: InitializeResult = receive_response(&client);
+    notify(&client, Initialized::METHOD, serde_json::json!({}));
+
+    notify(
+        &client,
+        DidOpenTextDocument::METHOD,
+        serde_json::json!({ "textDocument": 5 }),
+    );
+    let Message::Notification(notification) = client.receiver.recv().unwrap() else {
+        panic!("expected a notification");
+    };
+    assert_eq!(notification.method, LogMessage::METHOD);
+    let logged: LogMessageParams = serde_json::from_value(notification.params).unwrap();
+    assert_eq!(logged.typ, MessageType::ERROR);
+    assert!(logged
+        .message
+        .starts_with("failed to handle textDocument/didOpen"));
+
+    let uri = Url::parse("file:///specs/token.spec").unwrap();
+    notify(
+        &client,
+        DidOpenTextDocument::METHOD,
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "cvl".to_string(),
+                1,
+                "rule r { assert true; }\n".to_string(),
+            ),
+        },
+    );
+    let published = receive_diagnostics(&client);
+    assert_eq!(published.uri, uri);
+
+    request(&client, 2, Shutdown::METHOD, ());
+    let () = receive_response(&client);
+    notify(&client, Exit::METHOD, ());
+
+    server.join().unwrap().unwrap();
+}
+
+#[test]
+fn open_documents_are_checked_with_their_imports() {
+    let (server, client) = Connection::memory();
+    let server = thread::spawn(move || run(server));
+
+    request(&client, 1, Initialize::METHOD, InitializeParams::default());
+    let _: InitializeResult = receive_response(&client);
+    notify(&client, Initialized::METHOD, serde_json::json!({}));
+
+    let open = |uri: &Url, text: &str| {
+        let document = TextDocumentItem::new(uri.clone(), "cvl".to_string(), 1, text.to_string());
+        let params = DidOpenTextDocumentParams {
+            text_document: document,
+        };
+        notify(&client, DidOpenTextDocument::METHOD, params);
+        receive_diagnostics(&client).diagnostics
+    };
+
+    // neither document is on disk, so the import is only found among the open documents
+    let lib = Url::parse("file:///no-such-dir/lib.spec").unwrap();
+    let diagnostics = open(
+        &lib,
+        "/// @deprecated\ndefinition oldFee(uint x) returns uint = x;\n",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "definition oldFee is never used");
+
+    let main = Url::parse("file:///no-such-dir/main.spec").unwrap();
+    let diagnostics = open(
+        &main,
+        "import \"lib.spec\";\nimport \"other.spec\";\n\nrule r { assert oldFee(1) == 1; }\nuse rule missing;\n",
+    );
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            (3, "oldFee is deprecated"),
+            (
+                1,
+                "cannot find imported file other.spec. tried: /no-such-dir/other.spec"
+            ),
+            (4, "no rule named missing was found"),
+        ]
+    );
+
+    request(&client, 2, Shutdown::METHOD, ());
+    let () = receive_response(&client);
+    notify(&client, Exit::METHOD, ());
+
+    server.join().unwrap().unwrap();
+}
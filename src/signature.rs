@@ -0,0 +1,196 @@
+//! canonical one-line headers of elements, as shown in hovers, outlines and documentation.
+
+use crate::{Ast, Param};
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[cfg(test)]
+mod tests;
+
+/// spacing around punctuation, in the order it is applied
+static SPACING: Lazy<[(Regex, &str); 4]> = Lazy::new(|| {
+    [
+        (Regex::new(r"\s*=\s*>\s*").unwrap(), " => "),
+        (Regex::new(r"\s*,\s*").unwrap(), ", "),
+        (Regex::new(r"\s*([(\[])\s*").unwrap(), "$1"),
+        (Regex::new(r"\s*([)\]])").unwrap(), "$1"),
+    ]
+});
+
+/// collapses whitespace, and normalizes the spacing around brackets, commas and arrows.
+/// `mapping( address=>uint )` becomes `mapping(address => uint)`.
+pub(crate) fn normalize_type(text: &str) -> String {
+    let collapsed = text.split_whitespace().join(" ");
+
+    SPACING
+        .iter()
+        .fold(collapsed, |text, (pattern, replacement)| {
+            pattern.replace_all(&text, *replacement).into_owned()
+        })
+}
+
+fn param(param: &Param) -> String {
+    format!("{} {}", normalize_type(&param.ty), param.name)
+}
+
+fn param_list(params: &[Param]) -> String {
+    params.iter().map(param).join(", ")
+}
+
+fn persistent(is_persistent: bool) -> &'static str {
+    if is_persistent {
+        "persistent "
+    } else {
+        ""
+    }
+}
+
+impl Ast {
+    /// a canonical one-line header of the element. the bodies of rules, functions, hooks and
+    /// other declarations are left out, while blocks that are part of the header, such as
+    /// `filtered` blocks, the `methods` block and the proof of a `use invariant`, are elided as `{...}`.
+    /// for a freeform comment, this is its first line.
+    pub fn signature(&self) -> String {
+        match self {
+            Ast::FreeFormComment { text } => text
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default()
+                .split_whitespace()
+                .join(" "),
+            Ast::Rule {
+                name,
+                params,
+                filters,
+                ..
+            } => {
+                let filtered = if filters.is_some() {
+                    " filtered {...}"
+                } else {
+                    ""
+                };
+                format!("rule {name}({}){filtered}", param_list(params))
+            }
+            Ast::Invariant {
+                name,
+                params,
+                filters,
+                ..
+            } => {
+                let filtered = if filters.is_some() {
+                    " filtered {...}"
+                } else {
+                    ""
+                };
+                format!("invariant {name}({}){filtered}", param_list(params))
+            }
+            Ast::Function {
+                name,
+                params,
+                returns,
+                ..
+            } => {
+                let returns = match returns {
+                    Some(returns) => format!(" returns {}", normalize_type(returns)),
+                    None => String::new(),
+                };
+                format!("function {name}({}){returns}", param_list(params))
+            }
+            Ast::Definition {
+                name,
+                params,
+                returns,
+                ..
+            } => format!(
+                "definition {name}({}) returns {}",
+                param_list(params),
+                normalize_type(returns)
+            ),
+            Ast::GhostFunction {
+                persistent: is_persistent,
+                name,
+                ty_list,
+                returns,
+                ..
+            } => format!(
+                "{}ghost {name}({}) returns {}",
+                persistent(*is_persistent),
+                ty_list.iter().map(|ty| normalize_type(ty)).join(", "),
+                normalize_type(returns)
+            ),
+            Ast::GhostMapping {
+                persistent: is_persistent,
+                name,
+                mapping,
+                ..
+            } => format!(
+                "{}ghost {} {name}",
+                persistent(*is_persistent),
+                normalize_type(mapping)
+            ),
+            Ast::Methods { .. } => "methods {...}".to_string(),
+            Ast::Import { imported } => format!("import \"{imported}\""),
+            Ast::Using {
+                contract_name,
+                spec_name,
+            } => format!("using {contract_name} as {spec_name}"),
+            Ast::UseRule { name, filters } => {
+                let filtered = if filters.is_some() {
+                    " filtered {...}"
+                } else {
+                    ""
+                };
+                format!("use rule {name}{filtered}")
+            }
+            Ast::UseBuiltinRule { name } => format!("use builtin rule {name}"),
+            Ast::UseInvariant { name, proof } => {
+                let proof = if proof.is_some() { " {...}" } else { "" };
+                format!("use invariant {name}{proof}")
+            }
+            Ast::HookSload {
+                loaded,
+                slot_pattern,
+                ..
+            } => format!(
+                "hook Sload {} {} STORAGE",
+                param(loaded),
+                normalize_type(slot_pattern)
+            ),
+            Ast::HookSstore {
+                stored,
+                old,
+                slot_pattern,
+                ..
+            } => {
+                let old = match old {
+                    Some(old) => format!(" ({})", param(old)),
+                    None => String::new(),
+                };
+                format!(
+                    "hook Sstore {} {}{old} STORAGE",
+                    normalize_type(slot_pattern),
+                    param(stored)
+                )
+            }
+            Ast::HookCreate { created, .. } => format!("hook Create({})", param(created)),
+            Ast::HookOpcode {
+                opcode,
+                params,
+                returns,
+                ..
+            } => {
+                let params = if params.is_empty() {
+                    String::new()
+                } else {
+                    format!("({})", param_list(params))
+                };
+                let returns = match returns {
+                    Some(returns) => format!(" {}", param(returns)),
+                    None => String::new(),
+                };
+                format!("hook {opcode}{params}{returns}")
+            }
+        }
+    }
+}
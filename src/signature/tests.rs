@@ -0,0 +1,73 @@
+use super::normalize_type;
+use crate::parse::builder::Builder;
+use indoc::indoc;
+
+#[test]
+fn signatures_of_every_kind() {
+    let src = indoc! {"
+        import \"erc20.spec\";
+        using  Token   as token;
+
+        methods {
+            function balanceOf(address) external returns (uint) envfree;
+        }
+
+        rule transferPreservesSupply(address a, uint256   x) filtered { f -> !f.isView } { }
+        rule noParams { }
+        invariant solvency(env e) totalSupply() >= 0;
+        function clamp(uint x, uint max) returns uint { return x; }
+        definition half(uint x) returns uint = x / 2;
+        persistent ghost mapping( address=>uint ) g;
+        ghost sumOf(uint, address) returns mathint;
+
+        hook Sstore balances[KEY address a] uint256 v (uint256 oldValue) STORAGE { }
+        hook Sload uint256 v balances[KEY address a] STORAGE { }
+        hook Create(address created) { }
+        hook CALL(uint g, address addr, uint value, uint argsOffset, uint argsLength, uint retOffset, uint retLength) uint rc { }
+
+        use rule transferPreservesSupply;
+        use builtin rule sanity;
+        use invariant solvency { preserved { } }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let signatures = elements
+        .iter()
+        .map(|element| element.ast.signature())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        signatures,
+        [
+            "import \"erc20.spec\"",
+            "using Token as token",
+            "methods {...}",
+            "rule transferPreservesSupply(address a, uint256 x) filtered {...}",
+            "rule noParams()",
+            "invariant solvency(env e)",
+            "function clamp(uint x, uint max) returns uint",
+            "definition half(uint x) returns uint",
+            "persistent ghost mapping(address => uint) g",
+            "ghost sumOf(uint, address) returns mathint",
+            "hook Sstore balances[KEY address a] uint256 v (uint256 oldValue) STORAGE",
+            "hook Sload uint256 v balances[KEY address a] STORAGE",
+            "hook Create(address created)",
+            "hook CALL(uint g, address addr, uint value, uint argsOffset, uint argsLength, uint retOffset, uint retLength) uint rc",
+            "use rule transferPreservesSupply",
+            "use builtin rule sanity",
+            "use invariant solvency {...}",
+        ]
+    );
+
+    // elements without a name are titled by their signature
+    assert_eq!(elements[1].title().as_deref(), Some("using Token as token"));
+    assert_eq!(elements[4].title().as_deref(), Some("noParams"));
+}
+
+#[test]
+fn types_are_normalized() {
+    assert_eq!(
+        normalize_type("mapping ( address  =>mapping(uint=> bool) )"),
+        "mapping(address => mapping(uint => bool))"
+    );
+    assert_eq!(normalize_type("uint256 [ ]"), "uint256[]");
+}
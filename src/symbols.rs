@@ -0,0 +1,228 @@
+//! a table of the declarations in a set of files, and name resolution over it.
+
+use crate::id::{element_ids, ElementId};
+use crate::project::SpecProject;
+use crate::references::Reference;
+use crate::{Ast, CvlElement, Param};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum SymbolKind {
+    Rule,
+    Invariant,
+    Definition,
+    Function,
+    GhostFunction,
+    GhostMapping,
+    /// the alias of a `using` statement
+    Using,
+}
+
+impl SymbolKind {
+    fn of(ast: &Ast) -> Option<SymbolKind> {
+        match ast {
+            Ast::Rule { .. } => Some(SymbolKind::Rule),
+            Ast::Invariant { .. } => Some(SymbolKind::Invariant),
+            Ast::Definition { .. } => Some(SymbolKind::Definition),
+            Ast::Function { .. } => Some(SymbolKind::Function),
+            Ast::GhostFunction { .. } => Some(SymbolKind::GhostFunction),
+            Ast::GhostMapping { .. } => Some(SymbolKind::GhostMapping),
+            Ast::Using { .. } => Some(SymbolKind::Using),
+            _ => None,
+        }
+    }
+
+    /// whether a name of this kind may be followed by an argument list
+    pub fn is_callable(self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Invariant
+                | SymbolKind::Definition
+                | SymbolKind::Function
+                | SymbolKind::GhostFunction
+        )
+    }
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            SymbolKind::Rule => "rule",
+            SymbolKind::Invariant => "invariant",
+            SymbolKind::Definition => "definition",
+            SymbolKind::Function => "function",
+            SymbolKind::GhostFunction => "ghost function",
+            SymbolKind::GhostMapping => "ghost mapping",
+            SymbolKind::Using => "using",
+        };
+
+        write!(f, "{kind}")
+    }
+}
+
+/// a declaration in the table
+#[derive(Debug, Clone)]
+pub struct Symbol<'a> {
+    pub name: &'a str,
+    pub kind: SymbolKind,
+    pub id: ElementId,
+    pub element: &'a CvlElement,
+}
+
+/// the declarations of a set of files, by name
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable<'a> {
+    symbols: Vec<Symbol<'a>>,
+    by_name: HashMap<&'a str, Vec<usize>>,
+}
+
+/// what an identifier refers to
+#[derive(Debug, Clone)]
+pub enum Resolution<'t, 'a> {
+    /// a parameter of the element the identifier is in
+    Parameter(&'a Param),
+    /// every declaration with the name, such as the overloads of a function.
+    /// for a call, only the declarations that may be called are included, if there are any.
+    Declarations(Vec<&'t Symbol<'a>>),
+    /// a member of a contract, accessed through its `using` alias, as in `erc20.balanceOf`
+    ContractMember {
+        alias: &'t Symbol<'a>,
+        member: String,
+    },
+}
+
+/// an identifier, and what it refers to
+#[derive(Debug, Clone)]
+pub struct ResolvedReference<'t, 'a> {
+    pub reference: Reference,
+    pub resolution: Resolution<'t, 'a>,
+}
+
+impl<'a> SymbolTable<'a> {
+    /// indexes the elements of each file, given with the path of the file
+    pub fn new(files: &[(&str, &'a [CvlElement])]) -> SymbolTable<'a> {
+        let mut table = SymbolTable::default();
+
+        for &(path, elements) in files {
+            for (element, id) in elements.iter().zip(element_ids(path, elements)) {
+                let Some(kind) = SymbolKind::of(&element.ast) else {
+                    continue;
+                };
+                let name = match &element.ast {
+                    Ast::Using { spec_name, .. } => spec_name.as_str(),
+                    ast => ast.name().expect("every declaration has a name"),
+                };
+
+                table
+                    .by_name
+                    .entry(name)
+                    .or_default()
+                    .push(table.symbols.len());
+                table.symbols.push(Symbol {
+                    name,
+                    kind,
+                    id,
+                    element,
+                });
+            }
+        }
+
+        table
+    }
+
+    /// the declarations visible in a file of the project: its own, and those of the files it imports
+    pub fn for_file(project: &'a SpecProject, file: usize) -> SymbolTable<'a> {
+        let files = std::iter::once(file)
+            .chain(project.transitive_imports(file))
+            .map(|index| {
+                let file = &project.files[index];
+                let path = file.path.to_str().unwrap_or_default();
+                (path, file.spec.elements.as_slice())
+            })
+            .collect::<Vec<_>>();
+
+        SymbolTable::new(&files)
+    }
+
+    pub fn symbols(&self) -> &[Symbol<'a>] {
+        &self.symbols
+    }
+
+    /// every declaration with the given name
+    pub fn lookup(&self, name: &str) -> Vec<&Symbol<'a>> {
+        self.by_name
+            .get(name)
+            .map(|indices| indices.iter().map(|&i| &self.symbols[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// resolves a single reference that appears in `element`
+    pub fn resolve_reference(
+        &self,
+        element: &'a CvlElement,
+        reference: &Reference,
+    ) -> Option<Resolution<'_, 'a>> {
+        let head = reference.head();
+
+        // parameters shadow every declaration, including `using` aliases
+        if let Some(param) = shadowing_param(element, head) {
+            return Some(Resolution::Parameter(param));
+        }
+
+        let declarations = self.lookup(head);
+
+        if let Some((_, member)) = reference.name.split_once('.') {
+            // a dotted name is either a member of a contract, or a field of a parameter
+            let alias = declarations
+                .into_iter()
+                .find(|symbol| symbol.kind == SymbolKind::Using)?;
+            let member = member.split('.').next().unwrap_or(member).to_string();
+            return Some(Resolution::ContractMember { alias, member });
+        }
+
+        let callable = declarations
+            .iter()
+            .copied()
+            .filter(|symbol| symbol.kind.is_callable())
+            .collect::<Vec<_>>();
+
+        let declarations = if reference.is_call && !callable.is_empty() {
+            callable
+        } else {
+            declarations
+        };
+
+        (!declarations.is_empty()).then_some(Resolution::Declarations(declarations))
+    }
+
+    /// resolves every identifier in the code of `element`: its body, its invariant
+    /// or definition, and its filters, as well as its own declaration and parameters.
+    /// identifiers that refer to nothing in the table, such as types and keywords, are left out.
+    /// local variables are not tracked, so they do not shadow declarations of the same name.
+    pub fn resolve(&self, element: &'a CvlElement) -> Vec<ResolvedReference<'_, 'a>> {
+        element
+            .references()
+            .into_iter()
+            .filter_map(|reference| {
+                let resolution = self.resolve_reference(element, &reference)?;
+                Some(ResolvedReference {
+                    reference,
+                    resolution,
+                })
+            })
+            .collect()
+    }
+}
+
+/// the parameter of `element` named `name`, which shadows any declaration of the same name
+pub(crate) fn shadowing_param<'a>(element: &'a CvlElement, name: &str) -> Option<&'a Param> {
+    element
+        .ast
+        .params()
+        .and_then(|params| params.iter().find(|param| param.name == name))
+}
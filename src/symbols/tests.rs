@@ -0,0 +1,138 @@
+use super::{Resolution, SymbolKind, SymbolTable};
+use crate::parse::builder::Builder;
+use crate::project::fs::InMemoryFileSystem;
+use crate::project::ProjectBuilder;
+use crate::util::ByteSpan;
+use indoc::indoc;
+
+#[test]
+fn names_resolve_to_declarations() {
+    let src = indoc! {"
+        using ERC20 as erc20;
+
+        ghost mapping(address => uint) balances;
+        definition MAX() returns uint = 1000;
+        function clamp(uint x) returns uint { return x; }
+        function clamp(uint x, uint max) returns uint { return x; }
+
+        invariant bounded(address a) balances[a] <= MAX();
+
+        rule r(env e, uint MAX) {
+            requireInvariant bounded(e.msg.sender);
+            uint y = clamp(erc20.balanceOf(e, e.msg.sender));
+            assert y <= MAX;
+        }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let table = SymbolTable::new(&[("token.spec", &elements)]);
+
+    let kinds = table
+        .symbols()
+        .iter()
+        .map(|symbol| (symbol.name, symbol.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("erc20", SymbolKind::Using),
+            ("balances", SymbolKind::GhostMapping),
+            ("MAX", SymbolKind::Definition),
+            ("clamp", SymbolKind::Function),
+            ("clamp", SymbolKind::Function),
+            ("bounded", SymbolKind::Invariant),
+            ("r", SymbolKind::Rule),
+        ]
+    );
+    assert_eq!(
+        table.lookup("clamp")[1].id.slug(),
+        "function-clamp-uint-uint"
+    );
+
+    let resolved = table.resolve(&elements[5]);
+    let described = resolved
+        .iter()
+        .map(|resolved| {
+            let text = resolved.reference.span.byte_slice(src).unwrap();
+            let target = match &resolved.resolution {
+                Resolution::Parameter(param) => format!("parameter {}", param.name),
+                Resolution::Declarations(symbols) => {
+                    format!("{} x{}", symbols[0].kind, symbols.len())
+                }
+                Resolution::ContractMember { alias, member } => {
+                    format!("{member} of {}", alias.name)
+                }
+            };
+            (text, target)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        described,
+        [
+            ("bounded", "invariant x1".to_string()),
+            ("a", "parameter a".to_string()),
+            ("balances", "ghost mapping x1".to_string()),
+            ("a", "parameter a".to_string()),
+            ("MAX", "definition x1".to_string()),
+        ]
+    );
+
+    let resolved = table.resolve(&elements[6]);
+    let described = resolved
+        .iter()
+        .map(|resolved| match &resolved.resolution {
+            Resolution::Parameter(param) => format!("parameter {}", param.name),
+            Resolution::Declarations(symbols) => format!("{} x{}", symbols[0].kind, symbols.len()),
+            Resolution::ContractMember { alias, member } => format!("{member} of {}", alias.name),
+        })
+        .collect::<Vec<_>>();
+
+    // the declaration itself is included. `MAX` is shadowed by the parameter of the rule
+    assert_eq!(
+        described,
+        [
+            "rule x1",
+            "parameter e",
+            "parameter MAX",
+            "invariant x1",
+            "parameter e",
+            "function x2",
+            "balanceOf of erc20",
+            "parameter e",
+            "parameter e",
+            "parameter MAX",
+        ]
+    );
+}
+
+#[test]
+fn tables_include_imported_declarations() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        "import \"lib.spec\";\nrule r { assert f(); }",
+    );
+    file_system.insert("/specs/lib.spec", "definition f() returns bool = true;");
+    file_system.insert("/specs/other.spec", "definition g() returns bool = true;");
+
+    let project = ProjectBuilder::new(["/specs/main.spec", "/specs/other.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let table = SymbolTable::for_file(&project, 0);
+
+    let names = table
+        .symbols()
+        .iter()
+        .map(|symbol| symbol.id.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["/specs/main.spec#rule-r", "/specs/lib.spec#definition-f"]
+    );
+
+    let main = &project.files[0].spec.elements[1];
+    let resolved = table.resolve(main);
+    assert_eq!(resolved.len(), 2);
+    assert_eq!(resolved[1].reference.name, "f");
+}
@@ -0,0 +1,176 @@
+//! warnings for definitions, functions and ghosts that nothing refers to.
+
+use crate::project::SpecProject;
+use crate::references::is_assigned_in;
+use crate::semantic::name_span;
+use crate::symbols::{Resolution, Symbol, SymbolKind, SymbolTable};
+use crate::util::{ByteSpan, RangeConverter, Span};
+use crate::{CvlElement, TagKind};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests;
+
+/// a `@dev` tag containing this marker suppresses the warnings of its element
+pub const ALLOW_UNUSED_MARKER: &str = "allow-unused";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnusedKind {
+    Definition,
+    Function,
+    Ghost,
+    /// a ghost that is assigned to, for example by a hook, but never read
+    GhostNeverRead,
+}
+
+impl UnusedKind {
+    /// the machine-readable code of the warning
+    pub fn code(self) -> &'static str {
+        match self {
+            UnusedKind::Definition => "unused-definition",
+            UnusedKind::Function => "unused-function",
+            UnusedKind::Ghost => "unused-ghost",
+            UnusedKind::GhostNeverRead => "ghost-never-read",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UnusedConfig {
+    /// names of declarations that are never reported
+    pub allow: HashSet<String>,
+}
+
+/// a declaration that nothing refers to
+#[derive(Debug, Clone)]
+pub struct UnusedDeclaration<'a> {
+    pub kind: UnusedKind,
+    pub element: &'a CvlElement,
+    /// the index of the file the declaration is in
+    pub file: usize,
+    /// the span of the name of the declaration
+    pub span: Span,
+}
+
+impl UnusedDeclaration<'_> {
+    pub fn to_diagnostic(&self, converter: &RangeConverter) -> Diagnostic {
+        let name = self.element.ast.name().unwrap_or_default();
+        let message = match self.kind {
+            UnusedKind::Definition => format!("definition {name} is never used"),
+            UnusedKind::Function => format!("function {name} is never used"),
+            UnusedKind::Ghost => format!("ghost {name} is never used"),
+            UnusedKind::GhostNeverRead => format!("ghost {name} is assigned to, but never read"),
+        };
+
+        Diagnostic {
+            range: converter.to_range(self.span.clone()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(self.kind.code().to_string())),
+            message,
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        }
+    }
+}
+
+/// finds the definitions, CVL functions and ghosts of `files` that are not referred to
+/// by any element other than themselves. ghosts that are only ever assigned to are
+/// reported as well. the files are given with their paths, as in [SymbolTable::new].
+pub fn unused_declarations<'a>(
+    files: &[(&str, &'a [CvlElement])],
+    config: &UnusedConfig,
+) -> Vec<UnusedDeclaration<'a>> {
+    let table = SymbolTable::new(files);
+
+    let mut read = HashSet::new();
+    let mut written = HashSet::new();
+
+    for &(_, elements) in files {
+        for element in elements {
+            let code = element
+                .element_span
+                .byte_slice(&element.src)
+                .unwrap_or_default();
+
+            for resolved in table.resolve(element) {
+                let Resolution::Declarations(symbols) = resolved.resolution else {
+                    continue;
+                };
+                let end = resolved.reference.span.end - element.element_span.start;
+                let is_assigned = is_assigned_in(code, end);
+
+                // references from a declaration to itself, such as recursion, are not uses
+                for symbol in symbols
+                    .into_iter()
+                    .filter(|symbol| !std::ptr::eq(symbol.element, element))
+                {
+                    let uses = if is_assigned { &mut written } else { &mut read };
+                    uses.insert(&symbol.id);
+                }
+            }
+        }
+    }
+
+    let is_suppressed = |element: &CvlElement, name: &str| {
+        config.allow.contains(name)
+            || element.doc.iter().any(|tag| {
+                tag.kind == TagKind::Dev && tag.description.contains(ALLOW_UNUSED_MARKER)
+            })
+    };
+
+    let file_of = |symbol: &Symbol| {
+        files
+            .iter()
+            .position(|&(path, elements)| {
+                path == symbol.id.path
+                    && elements
+                        .iter()
+                        .any(|element| std::ptr::eq(element, symbol.element))
+            })
+            .expect("every symbol is from one of the files")
+    };
+
+    table
+        .symbols()
+        .iter()
+        .filter(|symbol| !read.contains(&symbol.id))
+        .filter(|symbol| !is_suppressed(symbol.element, symbol.name))
+        .filter_map(|symbol| {
+            let is_written = written.contains(&symbol.id);
+            let kind = match symbol.kind {
+                SymbolKind::Definition => UnusedKind::Definition,
+                SymbolKind::Function => UnusedKind::Function,
+                SymbolKind::GhostFunction | SymbolKind::GhostMapping if is_written => {
+                    UnusedKind::GhostNeverRead
+                }
+                SymbolKind::GhostFunction | SymbolKind::GhostMapping => UnusedKind::Ghost,
+                _ => return None,
+            };
+
+            Some(UnusedDeclaration {
+                kind,
+                element: symbol.element,
+                file: file_of(symbol),
+                span: name_span(symbol.element, symbol.name),
+            })
+        })
+        .collect()
+}
+
+impl SpecProject {
+    /// [unused_declarations] of every file in the project.
+    /// [UnusedDeclaration::file] is an index into [SpecProject::files].
+    pub fn unused_declarations(&self, config: &UnusedConfig) -> Vec<UnusedDeclaration<'_>> {
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let path = file.path.to_str().unwrap_or_default();
+                (path, file.spec.elements.as_slice())
+            })
+            .collect::<Vec<_>>();
+
+        unused_declarations(&files, config)
+    }
+}
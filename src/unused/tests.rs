@@ -0,0 +1,120 @@
+use super::{unused_declarations, UnusedConfig, UnusedKind};
+use crate::parse::builder::Builder;
+use crate::project::fs::InMemoryFileSystem;
+use crate::project::ProjectBuilder;
+use crate::util::{ByteSpan, RangeConverter};
+use indoc::indoc;
+use lsp_types::NumberOrString;
+use ropey::Rope;
+
+#[test]
+fn declarations_that_nothing_refers_to() {
+    let src = indoc! {"
+        ghost mapping(address => uint) balances;
+        ghost mapping(address => uint) written;
+        ghost uint unusedGhost;
+
+        hook Sstore _balances[KEY address a] uint v (uint oldValue) STORAGE {
+            balances[a] = v;
+            written[a] = v;
+        }
+
+        definition MAX() returns uint = 1000;
+        definition unusedDefinition() returns bool = true;
+
+        /// @dev kept for the next version. allow-unused
+        definition suppressed() returns bool = true;
+
+        function recursive(uint x) returns uint {
+            return recursive(x);
+        }
+
+        rule r(address a) {
+            assert balances[a] <= MAX();
+        }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let unused = unused_declarations(&[("token.spec", &elements)], &UnusedConfig::default());
+
+    let found = unused
+        .iter()
+        .map(|unused| (unused.span.byte_slice(src).unwrap(), unused.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            ("written", UnusedKind::GhostNeverRead),
+            ("unusedGhost", UnusedKind::Ghost),
+            ("unusedDefinition", UnusedKind::Definition),
+            ("recursive", UnusedKind::Function),
+        ]
+    );
+
+    let converter = RangeConverter::new(Rope::from_str(src));
+    let diagnostic = unused[0].to_diagnostic(&converter);
+    assert_eq!(diagnostic.range.start.line, 1);
+    assert_eq!(
+        diagnostic.code,
+        Some(NumberOrString::String("ghost-never-read".to_string()))
+    );
+    assert_eq!(
+        diagnostic.message,
+        "ghost written is assigned to, but never read"
+    );
+
+    let config = UnusedConfig {
+        allow: ["unusedGhost", "recursive"].map(String::from).into(),
+    };
+    let unused = unused_declarations(&[("token.spec", &elements)], &config);
+    assert_eq!(unused.len(), 2);
+}
+
+#[test]
+fn comparisons_and_implications_are_reads() {
+    let src = indoc! {"
+        ghost mapping(address => bool) flagged;
+        ghost mapping(address => uint) balances;
+
+        hook Sstore _balances[KEY address a] uint v (uint oldValue) STORAGE {
+            flagged[a] = true;
+            balances[a] = v;
+        }
+
+        rule r(address a) {
+            assert flagged[a] => balances [a] == 0;
+        }
+    "};
+    let elements = Builder::new(src).build().unwrap();
+    let unused = unused_declarations(&[("token.spec", &elements)], &UnusedConfig::default());
+
+    assert!(
+        unused.is_empty(),
+        "unexpected unused declarations: {unused:?}"
+    );
+}
+
+#[test]
+fn uses_in_other_files_of_the_project() {
+    let mut file_system = InMemoryFileSystem::new();
+    file_system.insert(
+        "/specs/main.spec",
+        "import \"lib.spec\";\nrule r { assert isPositive(1); }",
+    );
+    file_system.insert(
+        "/specs/lib.spec",
+        indoc! {"
+            definition isPositive(uint x) returns bool = x > 0;
+            definition isZero(uint x) returns bool = x == 0;
+        "},
+    );
+
+    let project = ProjectBuilder::new(["/specs/main.spec"])
+        .with_file_system(file_system)
+        .build()
+        .unwrap();
+    let unused = project.unused_declarations(&UnusedConfig::default());
+
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].file, 1);
+    assert_eq!(unused[0].element.ast.name(), Some("isZero"));
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "cli")]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+fn cvldoc(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cvldoc"))
+        .args(args)
+        .current_dir(fixtures())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn check_reports_errors_with_their_location() {
+    let output = cvldoc(&["check", "definition_test.spec"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "definition_test.spec:5:1: error: this tag is unsupported for definition blocks\n\
+         definition_test.spec:14:1: error: this tag is unsupported for definition blocks\n\
+         definition_test.spec:10:12: warning: definition harness_isListed is never used\n\
+         definition_test.spec:18:12: warning: definition MAX_UINT160 is never used\n"
+    );
+
+    let output = cvldoc(&["check", "project/lib"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn directories_and_globs_are_expanded() {
+    let output = cvldoc(&["outline", "project/cycle", "project/**/shared.spec"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "project/common/shared.spec\n  definition MAX\nproject/cycle/a.spec\nproject/cycle/b.spec\n"
+    );
+
+    let output = cvldoc(&["parse", "project/no-such-dir/*.spec"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(std::str::from_utf8(&output.stderr)
+        .unwrap()
+        .starts_with("error: no spec files found"));
+}
+
+#[test]
+fn parse_and_export() {
+    let output = cvldoc(&["parse", "project/lib/erc20.spec"]);
+    let parsed: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(parsed[0]["path"], "project/lib/erc20.spec");
+    assert_eq!(
+        parsed[0]["elements"][1]["ast"]["name"],
+        "transferPreservesSupply"
+    );
+
+    let output = cvldoc(&["export", "--format", "md", "project/lib/erc20.spec"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "# project/lib/erc20.spec\n\n<a id=\"project-lib-erc20-spec-rule-transferPreservesSupply\"></a>\n\n## transfers preserve the total supply\n\n```cvl\nrule transferPreservesSupply()\n```\n"
+    );
+
+    let output = cvldoc(&["export", "--format", "json", "project/common"]);
+    let exported: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(exported[0]["outline"]["elements"][0]["ast"]["name"], "MAX");
+}
@@ -0,0 +1 @@
+definition MAX() returns uint = 1000;
@@ -0,0 +1,4 @@
+import "../common/shared.spec";
+
+/// @title transfers preserve the total supply
+rule transferPreservesSupply { }
@@ -0,0 +1,6 @@
+import "lib/erc20.spec";
+import "shared.spec";
+import "missing.spec";
+import "cycle/a.spec";
+
+rule main { }